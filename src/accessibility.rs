@@ -0,0 +1,146 @@
+use bevy::{prelude::*, text::update_text2d_layout, ui::UiSystem};
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{powerups::PowerUp, settings::Settings, zombies::Zombie};
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            scale_text
+                .before(UiSystem::Layout)
+                .before(update_text2d_layout),
+        )
+        .add_systems(Update, tint_sprites);
+    }
+}
+
+// Colours telling zombie types and pickups apart, on the minimap and the edge arrows
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum ColorPalette {
+    #[default]
+    Default,
+    // Deuteranopia and protanopia, the most common ones
+    RedGreen,
+    // Tritanopia
+    BlueYellow,
+}
+
+impl ColorPalette {
+    pub fn zombie_color(&self, zombie: &Zombie) -> Color {
+        match (self, zombie) {
+            (ColorPalette::Default, Zombie::Chaser) => Color::rgb(0.9, 0.2, 0.2),
+            (ColorPalette::Default, Zombie::Crawler) => Color::rgb(0.9, 0.5, 0.1),
+            (ColorPalette::Default, Zombie::Bloater) => Color::rgb(0.7, 0.3, 0.9),
+            (ColorPalette::RedGreen, Zombie::Chaser) => Color::rgb(0.9, 0.6, 0.0),
+            (ColorPalette::RedGreen, Zombie::Crawler) => Color::rgb(0.35, 0.7, 0.9),
+            (ColorPalette::RedGreen, Zombie::Bloater) => Color::rgb(0.8, 0.6, 0.7),
+            (ColorPalette::BlueYellow, Zombie::Chaser) => Color::rgb(0.9, 0.2, 0.2),
+            (ColorPalette::BlueYellow, Zombie::Crawler) => Color::rgb(0.0, 0.6, 0.5),
+            (ColorPalette::BlueYellow, Zombie::Bloater) => Color::rgb(0.9, 0.6, 0.8),
+        }
+    }
+
+    pub fn pickup_color(&self, powerup: &PowerUp) -> Color {
+        match (self, powerup) {
+            (ColorPalette::Default, PowerUp::Health) => Color::rgb(0.3, 0.9, 0.3),
+            (ColorPalette::RedGreen, PowerUp::Health) => Color::rgb(0.0, 0.45, 0.7),
+            (ColorPalette::BlueYellow, PowerUp::Health) => Color::rgb(0.35, 0.7, 0.9),
+        }
+    }
+
+    // Translation key of the name shown in the settings
+    pub fn key(&self) -> &'static str {
+        match self {
+            ColorPalette::Default => "palette.default",
+            ColorPalette::RedGreen => "palette.red-green",
+            ColorPalette::BlueYellow => "palette.blue-yellow",
+        }
+    }
+}
+
+// How the `Shoot` action is held down
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum FireMode {
+    #[default]
+    Hold,
+    // One press starts shooting, the next one stops
+    Toggle,
+}
+
+impl FireMode {
+    // Translation key of the name shown in the settings
+    pub fn key(&self) -> &'static str {
+        match self {
+            FireMode::Hold => "fire-mode.hold",
+            FireMode::Toggle => "fire-mode.toggle",
+        }
+    }
+}
+
+// The original art is kept with the default palette, the colour-blind ones tint the sprites.
+// New sprites are tinted when spawned, every sprite when the palette changes
+fn tint_sprites(
+    settings: Res<Settings>,
+    mut zombies: Query<(Ref<Zombie>, &mut Sprite), Without<PowerUp>>,
+    mut powerups: Query<(Ref<PowerUp>, &mut Sprite), Without<Zombie>>,
+) {
+    let palette = settings.accessibility.palette;
+    let tint = |color: Color| {
+        if palette == ColorPalette::Default {
+            Color::WHITE
+        } else {
+            color
+        }
+    };
+
+    for (zombie, mut sprite) in zombies.iter_mut() {
+        if settings.is_changed() || zombie.is_added() {
+            sprite.color = tint(palette.zombie_color(&zombie));
+        }
+    }
+    for (powerup, mut sprite) in powerups.iter_mut() {
+        if settings.is_changed() || powerup.is_added() {
+            sprite.color = tint(palette.pickup_color(&powerup));
+        }
+    }
+}
+
+// Font sizes the text was spawned with, one per section, before the text scale
+#[derive(Component)]
+struct BaseFontSize(Vec<f32>);
+
+fn scale_text(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut new_texts: Query<(Entity, &mut Text), Without<BaseFontSize>>,
+    mut texts: Query<(&BaseFontSize, &mut Text)>,
+) {
+    let scale = settings.accessibility.text_scale;
+
+    for (entity, mut text) in new_texts.iter_mut() {
+        let sizes = text
+            .sections
+            .iter()
+            .map(|section| section.style.font_size)
+            .collect();
+        for section in text.sections.iter_mut() {
+            section.style.font_size *= scale;
+        }
+        commands.entity(entity).insert(BaseFontSize(sizes));
+    }
+
+    if !settings.is_changed() {
+        return;
+    }
+    for (base, mut text) in texts.iter_mut() {
+        for (section, size) in text.sections.iter_mut().zip(&base.0) {
+            if section.style.font_size != size * scale {
+                section.style.font_size = size * scale;
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{lens::UiPositionLens, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::Fonts,
+    combat::Health,
+    locale::Locale,
+    state::{gameplay_running, GameState, Overlay},
+    stats::RunStats,
+    storage,
+    survivour::{Downed, Survivour},
+    ui::spawn_info_screen,
+    waves::{Wave, ZombieCount},
+    zombies::{Zombie, ZombieKilled},
+};
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Achievements::load())
+            .insert_resource(storage::load::<AchievementProgress>(PROGRESS_FILE))
+            .init_resource::<WaveTracker>()
+            .add_event::<AchievementUnlocked>()
+            .add_systems(OnEnter(GameState::Playing), reset_wave_tracker)
+            .add_systems(Update, track_achievements.run_if(gameplay_running))
+            .add_systems(OnExit(GameState::Playing), save_progress)
+            .add_systems(Update, (show_toasts, despawn_toasts))
+            .add_systems(OnEnter(Overlay::Achievements), achievements_ui);
+    }
+}
+
+// The definitions ship with the game, only the progress is saved per player
+const ACHIEVEMENTS_DATA: &str = include_str!("../assets/data/achievements.ron");
+const PROGRESS_FILE: &str = "achievements.ron";
+const TOAST_WIDTH: f32 = 400.0;
+const TOAST_SLIDE: f32 = 0.4;
+const TOAST_DURATION: f32 = 3.0;
+
+#[derive(Deserialize)]
+enum Goal {
+    ReachWave(i32),
+    Kills { zombie: Zombie, count: i32 },
+    // No damage taken between the first and the last zombie of a wave
+    FlawlessWave,
+    // A survivour still standing with this much health or less when the wave ends
+    ClearWaveWithHealth(i32),
+}
+
+#[derive(Deserialize)]
+struct Achievement {
+    id: String,
+    goal: Goal,
+}
+
+impl Achievement {
+    fn name(&self, locale: &Locale) -> String {
+        locale.get(&format!("achievement.{}.name", self.id))
+    }
+
+    fn description(&self, locale: &Locale) -> String {
+        locale.get(&format!("achievement.{}.description", self.id))
+    }
+}
+
+#[derive(Resource, Deref)]
+struct Achievements(Vec<Achievement>);
+
+impl Achievements {
+    fn load() -> Self {
+        let achievements = ron::from_str(ACHIEVEMENTS_DATA).unwrap_or_else(|err| {
+            error!("Failed to read the achievements: {}", err);
+            Vec::new()
+        });
+        Self(achievements)
+    }
+}
+
+// Saved to disk, kills add up over every run
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct AchievementProgress {
+    unlocked: Vec<String>,
+    kills: ZombieCount,
+}
+
+impl AchievementProgress {
+    fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.unlocked.contains(&achievement.id)
+    }
+}
+
+// The value is the index of the achievement in `Achievements`
+#[derive(Event)]
+struct AchievementUnlocked(usize);
+
+// Follows the current wave, to know when one is cleared and how it went
+#[derive(Resource, Default)]
+struct WaveTracker {
+    zombies_left: i32,
+    damage_at_start: i32,
+}
+
+fn reset_wave_tracker(mut tracker: ResMut<WaveTracker>) {
+    *tracker = WaveTracker::default();
+}
+
+fn track_achievements(
+    mut kills: EventReader<ZombieKilled>,
+    wave: Res<Wave>,
+    zombie_count: Res<ZombieCount>,
+    stats: Res<RunStats>,
+    survivours: Query<&Health, (With<Survivour>, Without<Downed>)>,
+    mut tracker: ResMut<WaveTracker>,
+    achievements: Res<Achievements>,
+    mut progress: ResMut<AchievementProgress>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    for ZombieKilled(zombie) in kills.read() {
+        progress.kills.increase_count(zombie);
+    }
+
+    // A wave starts when its zombies spawn and is cleared when the last one dies
+    let zombies_left = zombie_count.total();
+    let wave_cleared = tracker.zombies_left > 0 && zombies_left == 0;
+    if tracker.zombies_left == 0 && zombies_left > 0 {
+        tracker.damage_at_start = stats.damage_taken;
+    }
+    tracker.zombies_left = zombies_left;
+
+    let mut any_unlocked = false;
+    for (index, achievement) in achievements.iter().enumerate() {
+        if progress.is_unlocked(achievement) {
+            continue;
+        }
+
+        let reached = match achievement.goal {
+            Goal::ReachWave(target) => wave.count >= target,
+            Goal::Kills { zombie, count } => progress.kills.count(zombie) >= count,
+            Goal::FlawlessWave => wave_cleared && stats.damage_taken == tracker.damage_at_start,
+            Goal::ClearWaveWithHealth(max_health) => {
+                wave_cleared && survivours.iter().any(|health| health.0 <= max_health)
+            }
+        };
+        if reached {
+            progress.unlocked.push(achievement.id.clone());
+            unlocked.send(AchievementUnlocked(index));
+            any_unlocked = true;
+        }
+    }
+
+    // Save right away, so an unlock isn't lost if the game is closed mid-run
+    if any_unlocked {
+        storage::save(PROGRESS_FILE, &*progress);
+    }
+}
+
+fn save_progress(progress: Res<AchievementProgress>) {
+    storage::save(PROGRESS_FILE, &*progress);
+}
+
+// All the stuff related to the unlock notifications
+#[derive(Component)]
+struct Toast;
+
+fn show_toasts(
+    mut commands: Commands,
+    mut unlocked: EventReader<AchievementUnlocked>,
+    achievements: Res<Achievements>,
+    toasts: Query<(), With<Toast>>,
+    fonts: Res<Fonts>,
+    locale: Res<Locale>,
+) {
+    // New toasts go below the ones still on screen
+    for (shown, AchievementUnlocked(index)) in (toasts.iter().count()..).zip(unlocked.read()) {
+        let achievement = &achievements[*index];
+        // The lens writes all four sides, the defaults would pin the toast to the left edge
+        let position = |right: f32| UiRect {
+            left: Val::Auto,
+            right: Val::Px(right),
+            top: Val::Px(80.0 + shown as f32 * 90.0),
+            bottom: Val::Auto,
+        };
+        let hidden = position(-TOAST_WIDTH - 20.0);
+        let visible = position(20.0);
+
+        // Slides in from the right edge, waits, then slides back out
+        let slide = Duration::from_secs_f32(TOAST_SLIDE);
+        let animation = Tween::new(
+            EaseFunction::QuadraticOut,
+            slide,
+            UiPositionLens {
+                start: hidden,
+                end: visible,
+            },
+        )
+        .then(Delay::new(Duration::from_secs_f32(TOAST_DURATION)))
+        .then(
+            Tween::new(
+                EaseFunction::QuadraticIn,
+                slide,
+                UiPositionLens {
+                    start: visible,
+                    end: hidden,
+                },
+            )
+            .with_completed_event(0),
+        );
+
+        commands
+            .spawn((
+                Toast,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: hidden.top,
+                        right: hidden.right,
+                        width: Val::Px(TOAST_WIDTH),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(12.0)),
+                        border: UiRect::left(Val::Px(4.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+                    border_color: Color::rgb(0.9, 0.7, 0.1).into(),
+                    z_index: ZIndex::Global(15),
+                    ..default()
+                },
+                Animator::new(animation),
+            ))
+            .with_children(|toast| {
+                toast.spawn(TextBundle::from_section(
+                    locale.format(
+                        "achievements.toast",
+                        &[("name", &achievement.name(&locale))],
+                    ),
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 24.0,
+                        color: Color::rgb(0.9, 0.7, 0.1),
+                    },
+                ));
+                toast.spawn(TextBundle::from_section(
+                    achievement.description(&locale),
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 20.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ));
+            });
+    }
+}
+
+fn despawn_toasts(
+    mut commands: Commands,
+    mut completed: EventReader<TweenCompleted>,
+    toasts: Query<(), With<Toast>>,
+) {
+    for event in completed.read() {
+        if toasts.contains(event.entity) {
+            commands.entity(event.entity).despawn_recursive();
+        }
+    }
+}
+
+fn achievements_ui(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    achievements: Res<Achievements>,
+    progress: Res<AchievementProgress>,
+    locale: Res<Locale>,
+) {
+    let unlocked = achievements
+        .iter()
+        .filter(|achievement| progress.is_unlocked(achievement))
+        .count();
+
+    let mut lines = vec![locale.format(
+        "achievements.progress",
+        &[("unlocked", &unlocked), ("total", &achievements.len())],
+    )];
+    for achievement in achievements.iter() {
+        let status = match achievement.goal {
+            _ if progress.is_unlocked(achievement) => locale.get("achievements.unlocked"),
+            Goal::Kills { zombie, count } => {
+                format!("{}/{}", progress.kills.count(zombie).min(count), count)
+            }
+            _ => locale.get("achievements.locked"),
+        };
+        lines.push(locale.format(
+            "achievements.entry",
+            &[
+                ("name", &achievement.name(&locale)),
+                ("description", &achievement.description(&locale)),
+                ("status", &status),
+            ],
+        ));
+    }
+
+    spawn_info_screen(
+        &mut commands,
+        &fonts,
+        &locale.get("achievements.title"),
+        &lines,
+    );
+}
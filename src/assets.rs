@@ -1,21 +1,36 @@
-use bevy::prelude::*;
+use bevy::{asset::LoadState, prelude::*, window::close_on_esc};
 use bevy_asset_loader::prelude::*;
-use bevy_kira_audio::prelude::{AudioSource, *};
+use bevy_kira_audio::prelude::AudioSource;
 
-use crate::state::GameState;
+use crate::{
+    locale::{Locale, LocalizedText},
+    state::GameState,
+};
 
 pub struct AssetsPlugin;
 
 impl Plugin for AssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(AudioPlugin);
         app.add_loading_state(
             LoadingState::new(GameState::Loading)
                 .continue_to_state(GameState::MainMenu)
                 .load_collection::<Graphics>()
                 .load_collection::<Sounds>()
-                .load_collection::<Fonts>(),
+                .load_collection::<Fonts>()
+                .on_failure_continue_to_state(GameState::AssetError),
         );
+
+        app.add_systems(
+            OnEnter(GameState::Loading),
+            (collect_loading_assets, spawn_loading_screen),
+        )
+        .add_systems(
+            Update,
+            update_loading_screen.run_if(in_state(GameState::Loading)),
+        )
+        .add_systems(OnExit(GameState::Loading), cleanup_loading_screen)
+        .add_systems(OnEnter(GameState::AssetError), asset_error_screen)
+        .add_systems(Update, close_on_esc.run_if(in_state(GameState::AssetError)));
     }
 }
 
@@ -75,3 +90,169 @@ pub struct Fonts {
     #[asset(path = "fonts/zombiecontrol.ttf")]
     pub zombiecontrol: Handle<Font>,
 }
+
+// Every handle of the collections above, to follow the loading
+#[derive(Resource, Deref)]
+struct LoadingAssets(Vec<UntypedHandle>);
+
+// The game font is one of the assets being loaded, so these screens use bevy's default font
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingBar;
+
+#[derive(Component)]
+struct LoadingText;
+
+// The handles are the same ones the loading state asks for, assets are only loaded once
+fn collect_loading_assets(world: &mut World) {
+    let mut handles = Graphics::load(world);
+    handles.extend(Sounds::load(world));
+    handles.extend(Fonts::load(world));
+    world.insert_resource(LoadingAssets(handles));
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            LoadingScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section("", text_style(56.0)),
+                LocalizedText::new("loading.title"),
+            ));
+            ui.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(400.0),
+                    height: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgb(0.15, 0.15, 0.15).into(),
+                ..default()
+            })
+            .with_children(|bar| {
+                bar.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: Color::rgb(0.35, 0.75, 0.35).into(),
+                        ..default()
+                    },
+                    LoadingBar,
+                ));
+            });
+            ui.spawn((TextBundle::from_section("", text_style(24.0)), LoadingText));
+        });
+}
+
+fn update_loading_screen(
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+    locale: Res<Locale>,
+    mut bar: Query<&mut Style, With<LoadingBar>>,
+    mut text: Query<&mut Text, With<LoadingText>>,
+) {
+    let loaded = loading
+        .iter()
+        .filter(|handle| asset_server.load_state(handle.id()) == LoadState::Loaded)
+        .count();
+    let progress = loaded as f32 / loading.len().max(1) as f32;
+
+    for mut style in bar.iter_mut() {
+        style.width = Val::Percent(progress * 100.0);
+    }
+    for mut text in text.iter_mut() {
+        text.sections[0].value = locale.format(
+            "loading.progress",
+            &[("loaded", &loaded), ("total", &loading.len())],
+        );
+    }
+}
+
+fn cleanup_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Names the files that failed, the game can't go on without them
+fn asset_error_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    loading: Res<LoadingAssets>,
+) {
+    let failed: Vec<String> = loading
+        .iter()
+        .filter(|handle| asset_server.load_state(handle.id()) == LoadState::Failed)
+        .map(|handle| {
+            asset_server
+                .get_path(handle.id())
+                .map(|path| path.to_string())
+                .unwrap_or_else(|| format!("{:?}", handle.id()))
+        })
+        .collect();
+    for path in failed.iter() {
+        error!("Failed to load the asset {}", path);
+    }
+
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font_size,
+        color,
+        ..default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        })
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section("", text_style(56.0, Color::rgb(0.9, 0.3, 0.2))),
+                LocalizedText::new("asset-error.title"),
+            ));
+            for path in failed {
+                ui.spawn((
+                    TextBundle::from_section("", text_style(24.0, Color::rgb(0.9, 0.9, 0.9))),
+                    LocalizedText::new("asset-error.file").with_arg("path", path),
+                ));
+            }
+            ui.spawn((
+                TextBundle::from_section("", text_style(24.0, Color::rgb(0.9, 0.9, 0.9))),
+                LocalizedText::new("asset-error.hint"),
+            ));
+        });
+}
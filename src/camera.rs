@@ -1,16 +1,380 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::camera::{CameraUpdateSystem, ScalingMode},
+    transform::TransformSystem,
+    utils::Duration,
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    game_conf::VIRTUAL_RESOLUTION,
+    map::MapBounds,
+    settings::Settings,
+    state::{gameplay_running, GameState},
+    survivour::{Crosshair, Survivour},
+    zombies::Zombie,
+};
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_world_camera);
+        app.add_plugins(InputManagerPlugin::<CameraActions>::default())
+            .init_resource::<ActionState<CameraActions>>()
+            .insert_resource(CameraActions::default_input_map());
+
+        app.add_event::<CameraTrauma>()
+            .add_event::<HitStop>()
+            .init_resource::<HitStopTimer>()
+            .init_resource::<TimeScale>();
+
+        app.add_systems(Startup, spawn_world_camera)
+            .add_systems(OnEnter(GameState::Playing), reset_camera)
+            .add_systems(
+                Update,
+                (add_trauma, start_hit_stop, end_hit_stop)
+                    .chain()
+                    .run_if(gameplay_running),
+            )
+            .add_systems(Update, zoom_camera.run_if(gameplay_running))
+            .add_systems(
+                Update,
+                apply_time_scale.run_if(resource_changed::<TimeScale>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (follow_survivour, screen_shake)
+                    .chain()
+                    .in_set(CameraFollow)
+                    .after(CameraUpdateSystem)
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(gameplay_running),
+            )
+            .add_systems(OnExit(GameState::Playing), cancel_hit_stop);
     }
 }
 
 #[derive(Component)]
 pub struct GameCamera;
 
+// Moves the camera in `PostUpdate`, anything placed relative to the view runs after it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraFollow;
+
+// Smoothed position the camera is moving towards the survivour with
+#[derive(Component, Default)]
+pub struct CameraRig {
+    pub position: Vec2,
+}
+
+// Manual zoom level picked by the player, 1 shows the virtual resolution
+#[derive(Component)]
+pub struct CameraZoom {
+    pub level: f32,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self { level: 1.0 }
+    }
+}
+
+#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
+pub enum CameraActions {
+    // Mouse wheel, zooms a step per notch
+    Zoom,
+    // Gamepad triggers, zoom continuously while held
+    ZoomIn,
+    ZoomOut,
+}
+
+impl CameraActions {
+    fn default_input_map() -> InputMap<CameraActions> {
+        let mut input_map = InputMap::<CameraActions>::default();
+
+        input_map.insert(SingleAxis::mouse_wheel_y(), CameraActions::Zoom);
+        input_map.insert(GamepadButtonType::RightTrigger, CameraActions::ZoomIn);
+        input_map.insert(GamepadButtonType::LeftTrigger, CameraActions::ZoomOut);
+
+        input_map
+    }
+}
+
+// Trauma goes from 0 to 1, the shake grows with its square
+#[derive(Component, Default)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+// Send this to shake the camera, the value is added to the current trauma
+#[derive(Event)]
+pub struct CameraTrauma(pub f32);
+
+// Send this to briefly freeze the game, e.g. on a big kill
+#[derive(Event)]
+pub struct HitStop(pub Duration);
+
+// Runs on real time, since the virtual clock is frozen during a hit-stop
+#[derive(Resource, Default)]
+struct HitStopTimer(Option<Timer>);
+
+// Speed of the game clock when no hit-stop is running, 1 is normal speed
+#[derive(Resource)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
 fn spawn_world_camera(mut cmds: Commands) {
-    cmds.spawn((GameCamera, Camera2dBundle::default()));
+    let mut camera = Camera2dBundle::default();
+    // Always show at least the virtual resolution, wider windows see a bit more of the map
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: VIRTUAL_RESOLUTION.x,
+        min_height: VIRTUAL_RESOLUTION.y,
+    };
+
+    cmds.spawn((
+        GameCamera,
+        CameraRig::default(),
+        CameraZoom::default(),
+        ScreenShake::default(),
+        camera,
+    ));
+}
+
+fn reset_camera(
+    mut camera: Query<
+        (
+            &mut Transform,
+            &mut CameraRig,
+            &mut CameraZoom,
+            &mut ScreenShake,
+            &mut OrthographicProjection,
+        ),
+        With<GameCamera>,
+    >,
+) {
+    let Ok((mut cam_tf, mut rig, mut zoom, mut shake, mut projection)) = camera.get_single_mut()
+    else {
+        return;
+    };
+
+    // The survivour always spawns in the middle of the map
+    rig.position = Vec2::ZERO;
+    zoom.level = 1.0;
+    projection.scale = 1.0;
+    shake.trauma = 0.0;
+    cam_tf.translation.x = 0.0;
+    cam_tf.translation.y = 0.0;
+    cam_tf.rotation = Quat::IDENTITY;
+}
+
+fn follow_survivour(
+    survivours: Query<(&Transform, &Crosshair), (With<Survivour>, Without<GameCamera>)>,
+    mut camera: Query<
+        (&mut Transform, &mut CameraRig, &OrthographicProjection),
+        (With<GameCamera>, Without<Survivour>),
+    >,
+    map_bounds: Query<&MapBounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let Some((min, max)) = survivours_bounds(survivours.iter().map(|(tf, _)| tf)) else {
+        return;
+    };
+    let Ok((mut cam_tf, mut rig, projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let Ok(map_bounds) = map_bounds.get_single() else {
+        return;
+    };
+    let settings = &settings.camera;
+
+    // Lean towards the crosshairs so the players see more of where they are aiming
+    let aim_offset = survivours
+        .iter()
+        .map(|(tf, crosshair)| crosshair.0 - tf.translation.truncate())
+        .sum::<Vec2>()
+        / survivours.iter().count() as f32;
+    let look_ahead = (aim_offset * settings.look_ahead).clamp_length_max(settings.max_look_ahead);
+    // Frame every survivour in co-op
+    let target = (min + max) / 2.0 + look_ahead;
+
+    // The camera only moves once the target leaves the deadzone
+    let half_deadzone = settings.deadzone / 2.0;
+    let offset = target - rig.position;
+    let mut desired = rig.position + offset - offset.clamp(-half_deadzone, half_deadzone);
+
+    // Clamp the target instead of the camera, so it eases into the map edges
+    let half_view = projection.area.half_size();
+    let max_position = (map_bounds.half_extents() - half_view).max(Vec2::ZERO);
+    desired = desired.clamp(-max_position, max_position);
+
+    // Exponential smoothing keeps the movement framerate independent
+    let blend = 1.0 - (-settings.smoothing * time.delta_seconds()).exp();
+    rig.position = rig.position.lerp(desired, blend);
+
+    cam_tf.translation.x = rig.position.x;
+    cam_tf.translation.y = rig.position.y;
+}
+
+// Corners of the box holding every survivour
+fn survivours_bounds<'a>(survivours: impl Iterator<Item = &'a Transform>) -> Option<(Vec2, Vec2)> {
+    survivours
+        .map(|tf| tf.translation.truncate())
+        .fold(None, |bounds, position| match bounds {
+            None => Some((position, position)),
+            Some((min, max)) => Some((min.min(position), max.max(position))),
+        })
+}
+
+fn zoom_camera(
+    mut camera: Query<(&mut CameraZoom, &mut OrthographicProjection), With<GameCamera>>,
+    survivours: Query<&Transform, With<Survivour>>,
+    zombies: Query<&Transform, With<Zombie>>,
+    actions: Res<ActionState<CameraActions>>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let Ok((mut zoom, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let settings = &settings.camera;
+
+    // Scrolling up zooms in, which means a smaller projection scale
+    zoom.level *= (-actions.value(CameraActions::Zoom) * 0.1).exp();
+    if actions.pressed(CameraActions::ZoomIn) {
+        zoom.level *= (-time.delta_seconds()).exp();
+    }
+    if actions.pressed(CameraActions::ZoomOut) {
+        zoom.level *= time.delta_seconds().exp();
+    }
+    zoom.level = zoom.level.clamp(settings.min_zoom, settings.max_zoom);
+
+    let mut target = zoom.level;
+    if settings.auto_zoom {
+        let close_zombies = zombies
+            .iter()
+            .filter(|zombie_tf| {
+                survivours.iter().any(|survivour_tf| {
+                    zombie_tf
+                        .translation
+                        .truncate()
+                        .distance(survivour_tf.translation.truncate())
+                        < settings.auto_zoom_radius
+                })
+            })
+            .count();
+        target += close_zombies as f32 * settings.auto_zoom_per_zombie;
+    }
+    target = target.clamp(settings.min_zoom, settings.max_zoom);
+
+    // Zoom out further than usual if that's what it takes to see every survivour
+    if let Some((min, max)) = survivours_bounds(survivours.iter()) {
+        let unscaled_view = projection.area.size() / projection.scale;
+        let needed = (max - min + settings.frame_margin) / unscaled_view;
+        target = target.max(needed.max_element().min(settings.frame_max_zoom));
+    }
+
+    let blend = 1.0 - (-settings.smoothing * time.delta_seconds()).exp();
+    projection.scale += (target - projection.scale) * blend;
+}
+
+fn add_trauma(
+    mut events: EventReader<CameraTrauma>,
+    mut shake: Query<&mut ScreenShake, With<GameCamera>>,
+    settings: Res<Settings>,
+) {
+    let Ok(mut shake) = shake.get_single_mut() else {
+        return;
+    };
+
+    let intensity = settings.accessibility.screen_shake;
+    for trauma in events.read() {
+        shake.trauma = (shake.trauma + trauma.0 * intensity).clamp(0.0, 1.0);
+    }
+}
+
+// Must run after `follow_survivour`, which resets the camera position every frame
+fn screen_shake(
+    mut camera: Query<(&mut Transform, &mut ScreenShake), With<GameCamera>>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+) {
+    let Ok((mut cam_tf, mut shake)) = camera.get_single_mut() else {
+        return;
+    };
+    let settings = &settings.camera;
+
+    shake.trauma = (shake.trauma - settings.shake_decay * time.delta_seconds()).max(0.0);
+    let strength = shake.trauma * shake.trauma;
+
+    // A few out of phase sine waves make a cheap and smooth noise
+    let t = time.elapsed_seconds() * 30.0;
+    let noise = |seed: f32| ((t + seed).sin() + (t * 1.7 + seed * 3.1).sin()) / 2.0;
+
+    cam_tf.translation.x += settings.shake_offset * strength * noise(0.0);
+    cam_tf.translation.y += settings.shake_offset * strength * noise(10.0);
+    cam_tf.rotation = Quat::from_rotation_z(settings.shake_rotation * strength * noise(20.0));
+}
+
+fn start_hit_stop(
+    mut events: EventReader<HitStop>,
+    mut hit_stop: ResMut<HitStopTimer>,
+    mut time: ResMut<Time<Virtual>>,
+    settings: Res<Settings>,
+) {
+    for stop in events.read() {
+        if !settings.accessibility.hit_stop {
+            continue;
+        }
+        // Never shorten a freeze that is already running
+        let remaining = hit_stop
+            .0
+            .as_ref()
+            .map(Timer::remaining)
+            .unwrap_or_default();
+        hit_stop.0 = Some(Timer::new(stop.0.max(remaining), TimerMode::Once));
+        time.set_relative_speed(0.0);
+    }
+}
+
+fn end_hit_stop(
+    mut hit_stop: ResMut<HitStopTimer>,
+    mut time: ResMut<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    time_scale: Res<TimeScale>,
+) {
+    let Some(timer) = hit_stop.0.as_mut() else {
+        return;
+    };
+
+    if timer.tick(real_time.delta()).finished() {
+        hit_stop.0 = None;
+        time.set_relative_speed(time_scale.0);
+    }
+}
+
+fn cancel_hit_stop(
+    mut hit_stop: ResMut<HitStopTimer>,
+    mut time: ResMut<Time<Virtual>>,
+    time_scale: Res<TimeScale>,
+) {
+    hit_stop.0 = None;
+    time.set_relative_speed(time_scale.0);
+}
+
+// Pausing stops the virtual clock without touching its speed, so only hit-stops need care
+fn apply_time_scale(
+    hit_stop: Res<HitStopTimer>,
+    mut time: ResMut<Time<Virtual>>,
+    time_scale: Res<TimeScale>,
+) {
+    // The hit-stop restores the new scale once it ends
+    if hit_stop.0.is_none() {
+        time.set_relative_speed(time_scale.0);
+    }
 }
@@ -2,17 +2,21 @@ use std::ops::Mul;
 
 use bevy::prelude::*;
 use bevy::sprite::collide_aabb;
-use bevy_kira_audio::prelude::*;
 use bevy_tweening::{lens::TransformPositionLens, *};
 
 use crate::{
-    assets::{Graphics, Sounds},
-    combat::{AttackDelay, CombatBundle, Health},
+    assets::Graphics,
+    camera::{CameraTrauma, HitStop},
+    combat::{AttackDelay, CombatBundle, Health, Invulnerable, MaxHealth},
+    locale::Locale,
+    popups::{Popup, DAMAGE_COLOR, PICKUP_COLOR, SCORE_COLOR},
     powerups::{PowerUp, PowerupSpawnChance, PowerupTimer},
-    state::GameState,
-    survivour::{Bullet, Survivour},
+    sound::{PlaySfx, Sfx},
+    state::{gameplay_running, GameState},
+    stats::RunStats,
+    survivour::{Bullet, Dodging, Downed, Survivour},
     waves::{Score, ZombieCount},
-    zombies::Zombie,
+    zombies::{Zombie, ZombieKilled},
 };
 
 pub struct CollisionPlugin;
@@ -25,15 +29,16 @@ impl Plugin for CollisionPlugin {
             Update,
             (
                 collision_zombies_bullets,
-                survivour_health_pickup,
+                survivour_pickup,
                 collision_zombies_survivour,
             )
-                .run_if(in_state(GameState::Playing)),
+                .run_if(gameplay_running),
         )
         .add_systems(
             Update,
-            (despawn_blood, despawn_powerup).run_if(in_state(GameState::Playing)),
-        );
+            (despawn_blood, despawn_powerup).run_if(gameplay_running),
+        )
+        .add_systems(OnExit(GameState::Playing), cleanup_powerups);
     }
 }
 
@@ -43,6 +48,8 @@ pub struct CollisionSize(pub Vec2);
 #[derive(Component, Deref, DerefMut)]
 pub struct BloodTimer(pub Timer);
 
+const BULLET_DAMAGE: i32 = 1;
+
 fn collision_zombies_bullets(
     mut commands: Commands,
     mut zombies: Query<(
@@ -55,11 +62,18 @@ fn collision_zombies_bullets(
     )>,
     bullets: Query<(Entity, &Transform, &CollisionSize, &Bullet)>,
     graphics: Res<Graphics>,
-    sounds: Res<Sounds>,
-    audio: Res<Audio>,
+    mut sfx: EventWriter<PlaySfx>,
     mut zombie_count: ResMut<ZombieCount>,
     mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut trauma: EventWriter<CameraTrauma>,
+    mut hit_stop: EventWriter<HitStop>,
+    mut popups: EventWriter<Popup>,
+    mut killed: EventWriter<ZombieKilled>,
 ) {
+    // A bullet overlapping several zombies only hits the first one
+    let mut spent_bullets = Vec::new();
+
     for (
         zombie_entity,
         zombie_transform,
@@ -70,6 +84,9 @@ fn collision_zombies_bullets(
     ) in zombies.iter_mut()
     {
         for (bullet_entity, bullet_transform, bullet_size, _) in bullets.iter() {
+            if spent_bullets.contains(&bullet_entity) {
+                continue;
+            }
             let collision = collide_aabb::collide(
                 zombie_transform.translation,
                 zombie_size.0,
@@ -80,16 +97,35 @@ fn collision_zombies_bullets(
             if collision.is_none() {
                 continue;
             }
-            audio.play(sounds.splat.clone());
+            sfx.send(PlaySfx(Sfx::Splat));
             commands.entity(bullet_entity).despawn();
-            zombie_health.0 -= 1;
+            spent_bullets.push(bullet_entity);
+            zombie_health.0 -= BULLET_DAMAGE;
+            stats.shots_hit += 1;
+            let position = zombie_transform.translation.truncate();
 
             if zombie_health.0 > 0 {
+                popups.send(Popup::new(
+                    position,
+                    BULLET_DAMAGE.to_string(),
+                    DAMAGE_COLOR,
+                ));
                 continue;
             }
             zombie_count.decrease_count(zombie);
             score.increase(zombie);
-            health_pickup_spawn(
+            popups.send(Popup::new(
+                position,
+                format!("+{}", Score::points(zombie)),
+                SCORE_COLOR,
+            ));
+            stats.add_kill(zombie);
+            killed.send(ZombieKilled(*zombie));
+            if let Zombie::Bloater = zombie {
+                trauma.send(CameraTrauma(0.5));
+                hit_stop.send(HitStop(std::time::Duration::from_secs_f32(0.08)));
+            }
+            powerup_spawn(
                 &mut commands,
                 &graphics,
                 power_spawn_chance,
@@ -114,59 +150,72 @@ fn collision_zombies_bullets(
     }
 }
 
-fn health_pickup_spawn(
+fn powerup_spawn(
     commands: &mut Commands,
     graphics: &Res<Graphics>,
     power_spawn_chance: &PowerupSpawnChance,
     bullet_transform: &Transform,
     zombie_transform: &Transform,
 ) {
-    if rand::random::<f32>() < power_spawn_chance.health {
-        let zombie_pos = zombie_transform.translation;
+    if rand::random::<f32>() >= power_spawn_chance.health {
+        return;
+    }
 
-        let direction = bullet_transform
-            .rotation
-            .mul(Vec3::X)
-            .normalize()
-            .truncate();
+    let zombie_pos = zombie_transform.translation;
 
-        let tween = Tween::new(
-            EaseFunction::ExponentialOut,
-            std::time::Duration::from_secs_f32(0.5),
-            TransformPositionLens {
-                start: Vec3::new(zombie_pos.x, zombie_pos.y, 1.75),
-                end: Vec3::new(
-                    zombie_pos.x + direction.x * 25.0,
-                    zombie_pos.y + direction.y * 25.0,
-                    1.75,
-                ),
-            },
-        );
+    let direction = bullet_transform
+        .rotation
+        .mul(Vec3::X)
+        .normalize()
+        .truncate();
 
-        commands.spawn((
-            SpriteBundle {
-                texture: graphics.health_pickup.clone(),
-                transform: Transform::from_translation(zombie_pos.truncate().extend(1.75)),
-                ..default()
-            },
-            PowerUp::Health,
-            Animator::new(tween),
-            PowerupTimer(Timer::from_seconds(60.0, TimerMode::Once)),
-            CollisionSize(Vec2::new(32.0, 26.0)),
-        ));
-    }
+    let tween = Tween::new(
+        EaseFunction::ExponentialOut,
+        std::time::Duration::from_secs_f32(0.5),
+        TransformPositionLens {
+            start: Vec3::new(zombie_pos.x, zombie_pos.y, 1.75),
+            end: Vec3::new(
+                zombie_pos.x + direction.x * 25.0,
+                zombie_pos.y + direction.y * 25.0,
+                1.75,
+            ),
+        },
+    );
+
+    commands.spawn((
+        SpriteBundle {
+            texture: graphics.health_pickup.clone(),
+            transform: Transform::from_translation(zombie_pos.truncate().extend(1.75)),
+            ..default()
+        },
+        PowerUp::Health,
+        Animator::new(tween),
+        PowerupTimer(Timer::from_seconds(60.0, TimerMode::Once)),
+        CollisionSize(Vec2::new(32.0, 26.0)),
+    ));
 }
 
-fn survivour_health_pickup(
+fn survivour_pickup(
     mut commands: Commands,
-    mut survivour: Query<(&Transform, &CollisionSize, &mut Health), With<Survivour>>,
+    mut survivour: Query<
+        (&Transform, &CollisionSize, &mut Health, &MaxHealth),
+        (With<Survivour>, Without<Downed>),
+    >,
 
     powerups: Query<(Entity, &Transform, &CollisionSize, &PowerUp)>,
-    audio: Res<Audio>,
-    sounds: Res<Sounds>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut stats: ResMut<RunStats>,
+    mut popups: EventWriter<Popup>,
+    locale: Res<Locale>,
 ) {
-    for (sv_tf, player_size, mut health) in survivour.iter_mut() {
+    // In co-op both survivours can stand on the same pickup
+    let mut picked_up = Vec::new();
+
+    for (sv_tf, player_size, mut health, max_health) in survivour.iter_mut() {
         for (entity, powerup_transform, powerup_size, powerup) in powerups.iter() {
+            if picked_up.contains(&entity) {
+                continue;
+            }
             let collision = collide_aabb::collide(
                 sv_tf.translation,
                 player_size.0,
@@ -177,18 +226,22 @@ fn survivour_health_pickup(
             if collision.is_none() {
                 continue;
             }
-            match powerup {
-                PowerUp::Health => {
-                    health.0 += if health.0 < 5 {
-                        1
-                    } else {
-                        warn!("Health not updated!");
-                        0
-                    };
-                    audio.play(sounds.pickup.clone());
-                    commands.entity(entity).despawn();
+            let text = match powerup {
+                PowerUp::Health if health.0 < max_health.0 => {
+                    health.0 += 1;
+                    locale.get("popup.health")
                 }
-            }
+                PowerUp::Health => locale.get("popup.max-health"),
+            };
+            popups.send(Popup::new(
+                powerup_transform.translation.truncate(),
+                text,
+                PICKUP_COLOR,
+            ));
+            sfx.send(PlaySfx(Sfx::Pickup));
+            commands.entity(entity).despawn();
+            picked_up.push(entity);
+            stats.pickups_collected += 1;
         }
     }
 }
@@ -198,19 +251,27 @@ fn collision_zombies_survivour(
         (&Transform, &CollisionSize, &mut AttackDelay),
         (With<Zombie>, Without<Survivour>),
     >,
+    // Rolling survivours go through zombies unharmed
     mut survivour: Query<
-        (&Transform, &CollisionSize, &mut Health),
-        (With<Survivour>, Without<Zombie>),
+        (Entity, &Transform, &CollisionSize, &mut Health),
+        (
+            With<Survivour>,
+            Without<Zombie>,
+            Without<Dodging>,
+            Without<Downed>,
+            Without<Invulnerable>,
+        ),
     >,
-    audio: Res<Audio>,
-    sounds: Res<Sounds>,
-    mut game_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+    mut sfx: EventWriter<PlaySfx>,
+    mut trauma: EventWriter<CameraTrauma>,
+    mut stats: ResMut<RunStats>,
     time: Res<Time>,
 ) {
     for (zombie_transform, zombie_size, mut attack_delay) in zombies.iter_mut() {
         attack_delay.tick(time.delta());
 
-        for (sv_tf, survivour_size, mut health) in survivour.iter_mut() {
+        for (sv_entity, sv_tf, survivour_size, mut health) in survivour.iter_mut() {
             let collision = collide_aabb::collide(
                 sv_tf.translation,
                 survivour_size.0,
@@ -223,11 +284,14 @@ fn collision_zombies_survivour(
             }
             if attack_delay.finished() {
                 health.0 -= 1;
-                audio.play(sounds.hit.clone());
+                stats.damage_taken += 1;
+                sfx.send(PlaySfx(Sfx::Hit));
+                trauma.send(CameraTrauma(0.4));
                 attack_delay.reset();
             }
+            // The run ends once every survivour is downed
             if health.0 <= 0 {
-                game_state.set(GameState::GameOver);
+                commands.entity(sv_entity).insert(Downed::default());
             }
         }
     }
@@ -256,3 +320,10 @@ fn despawn_powerup(
         }
     }
 }
+
+// Pickups left on the ground don't carry over to the next run
+fn cleanup_powerups(mut commands: Commands, powerups: Query<Entity, With<PowerUp>>) {
+    for entity in powerups.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
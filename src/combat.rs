@@ -13,3 +13,12 @@ pub struct CombatBundle {
     pub health: Health,
     pub attack_delay: AttackDelay,
 }
+
+// Zombies can't hurt this survivour, given by the dev console's god mode
+#[derive(Component)]
+#[cfg_attr(not(feature = "dev"), allow(dead_code))]
+pub struct Invulnerable;
+
+// Health pickups can't heal above this
+#[derive(Component, Deref, DerefMut)]
+pub struct MaxHealth(pub i32);
@@ -0,0 +1,470 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::{
+    assets::Fonts,
+    locale::{Locale, LocalizedText},
+    state::Overlay,
+    storage,
+    survivour::{InputDevices, Survivour, SurvivourActions, SurvivourBundle},
+    ui::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
+};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut controls = storage::load::<Controls>(CONTROLS_FILE);
+        controls.add_missing_defaults();
+
+        app.insert_resource(controls)
+            .init_resource::<Rebinding>()
+            .add_systems(
+                Update,
+                apply_controls.run_if(resource_changed::<Controls>()),
+            );
+
+        app.add_systems(OnEnter(Overlay::Controls), controls_ui)
+            .add_systems(
+                Update,
+                (
+                    capture_binding,
+                    controls_buttons,
+                    update_binding_labels,
+                    close_controls,
+                )
+                    .chain()
+                    .run_if(in_state(Overlay::Controls)),
+            )
+            .add_systems(OnExit(Overlay::Controls), cleanup_controls);
+    }
+}
+
+const CONTROLS_FILE: &str = "controls.ron";
+
+// Inputs the game reads directly, which no action can be bound to
+const RESERVED_BINDINGS: [Binding; 5] = [
+    // Pause and cancel
+    Binding::Key(KeyCode::Escape),
+    // Fullscreen
+    Binding::Key(KeyCode::F11),
+    // Dev console
+    Binding::Key(KeyCode::Grave),
+    // Menu confirm
+    Binding::Key(KeyCode::Return),
+    // Pause on a gamepad
+    Binding::Gamepad(GamepadButtonType::Start),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "{:?}", button),
+        }
+    }
+}
+
+// Each button action has one keyboard or mouse binding and one gamepad binding
+#[derive(Resource, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Controls {
+    pub keyboard: BTreeMap<SurvivourActions, Binding>,
+    pub gamepad: BTreeMap<SurvivourActions, Binding>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        use SurvivourActions::*;
+
+        Self {
+            keyboard: BTreeMap::from([
+                (Up, Binding::Key(KeyCode::W)),
+                (Down, Binding::Key(KeyCode::S)),
+                (Left, Binding::Key(KeyCode::A)),
+                (Right, Binding::Key(KeyCode::D)),
+                (Shoot, Binding::Mouse(MouseButton::Left)),
+                (Sprint, Binding::Key(KeyCode::ShiftLeft)),
+                (Dodge, Binding::Key(KeyCode::Space)),
+                (ToggleMinimap, Binding::Key(KeyCode::M)),
+            ]),
+            gamepad: BTreeMap::from([
+                (Up, Binding::Gamepad(GamepadButtonType::DPadUp)),
+                (Down, Binding::Gamepad(GamepadButtonType::DPadDown)),
+                (Left, Binding::Gamepad(GamepadButtonType::DPadLeft)),
+                (Right, Binding::Gamepad(GamepadButtonType::DPadRight)),
+                (Shoot, Binding::Gamepad(GamepadButtonType::RightTrigger2)),
+                (Sprint, Binding::Gamepad(GamepadButtonType::LeftThumb)),
+                (Dodge, Binding::Gamepad(GamepadButtonType::South)),
+                (ToggleMinimap, Binding::Gamepad(GamepadButtonType::Select)),
+            ]),
+        }
+    }
+}
+
+impl Controls {
+    // Actions added after the file was saved get their default binding
+    fn add_missing_defaults(&mut self) {
+        let defaults = Controls::default();
+        for (slot, default_slot) in [
+            (&mut self.keyboard, defaults.keyboard),
+            (&mut self.gamepad, defaults.gamepad),
+        ] {
+            for (action, binding) in default_slot {
+                // Unless the player already uses that input for something else
+                if !slot.contains_key(&action) && !slot.values().any(|used| *used == binding) {
+                    slot.insert(action, binding);
+                }
+            }
+        }
+    }
+
+    // Analog actions are bound to the sticks and can't be changed
+    fn is_rebindable(action: SurvivourActions) -> bool {
+        !matches!(action, SurvivourActions::Move | SurvivourActions::Aim)
+    }
+
+    fn slot(&self, slot: BindingSlot) -> &BTreeMap<SurvivourActions, Binding> {
+        match slot {
+            BindingSlot::Keyboard => &self.keyboard,
+            BindingSlot::Gamepad => &self.gamepad,
+        }
+    }
+
+    fn slot_mut(&mut self, slot: BindingSlot) -> &mut BTreeMap<SurvivourActions, Binding> {
+        match slot {
+            BindingSlot::Keyboard => &mut self.keyboard,
+            BindingSlot::Gamepad => &mut self.gamepad,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BindingSlot {
+    Keyboard,
+    Gamepad,
+}
+
+// The binding waiting for an input, if any
+#[derive(Resource, Default)]
+struct Rebinding(Option<(SurvivourActions, BindingSlot)>);
+
+fn apply_controls(
+    controls: Res<Controls>,
+    mut input_maps: Query<(&mut InputMap<SurvivourActions>, &InputDevices), With<Survivour>>,
+) {
+    for (mut input_map, devices) in input_maps.iter_mut() {
+        *input_map = SurvivourBundle::input_map(&controls, *devices);
+    }
+}
+
+// All the stuff related to the controls screen
+#[derive(Component)]
+pub struct ControlsScreen;
+
+#[derive(Component)]
+struct RebindButton {
+    action: SurvivourActions,
+    slot: BindingSlot,
+}
+
+#[derive(Component)]
+struct ControlsBackButton;
+
+#[derive(Component)]
+struct ControlsMessage;
+
+fn controls_ui(mut commands: Commands, fonts: Res<Fonts>) {
+    let text_style = |font_size: f32| TextStyle {
+        font: fonts.zombiecontrol.clone(),
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let button_style = Style {
+        width: Val::Px(260.0),
+        height: Val::Px(50.0),
+        margin: UiRect::horizontal(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ControlsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                // Draw on top of the screen that opened it
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section("", text_style(72.0)),
+                LocalizedText::new("controls.title"),
+            ));
+
+            for action in SurvivourActions::iter() {
+                ui.spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        TextBundle {
+                            text: Text::from_section("", text_style(32.0)),
+                            style: Style {
+                                width: Val::Px(160.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        LocalizedText::new(action.key()),
+                    ));
+
+                    if !Controls::is_rebindable(action) {
+                        let stick = match action {
+                            SurvivourActions::Move => "controls.left-stick",
+                            _ => "controls.right-stick",
+                        };
+                        row.spawn((
+                            TextBundle::from_section("", text_style(32.0)),
+                            LocalizedText::new(stick),
+                        ));
+                        return;
+                    }
+
+                    for slot in [BindingSlot::Keyboard, BindingSlot::Gamepad] {
+                        row.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            RebindButton { action, slot },
+                        ))
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section("", text_style(24.0)));
+                        });
+                    }
+                });
+            }
+
+            ui.spawn((
+                TextBundle::from_section("", text_style(24.0)),
+                ControlsMessage,
+            ));
+
+            ui.spawn((
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                ControlsBackButton,
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    TextBundle::from_section("", text_style(32.0)),
+                    LocalizedText::new("menu.back"),
+                ));
+            });
+        });
+}
+
+fn controls_buttons(
+    mut rebind_buttons: Query<
+        (&Interaction, &RebindButton, &mut BackgroundColor),
+        (Changed<Interaction>, Without<ControlsBackButton>),
+    >,
+    mut back_button: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ControlsBackButton>),
+    >,
+    mut rebinding: ResMut<Rebinding>,
+    mut message: Query<&mut Text, With<ControlsMessage>>,
+    mut overlay: ResMut<NextState<Overlay>>,
+    locale: Res<Locale>,
+) {
+    for (interaction, button, mut color) in &mut rebind_buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                rebinding.0 = Some((button.action, button.slot));
+
+                let key = match button.slot {
+                    BindingSlot::Keyboard => "controls.press-key",
+                    BindingSlot::Gamepad => "controls.press-button",
+                };
+                message.single_mut().sections[0].value =
+                    locale.format(key, &[("action", &locale.get(button.action.key()))]);
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+
+    for (interaction, mut color) in &mut back_button {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                overlay.set(Overlay::Settings);
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// Runs before `controls_buttons`, so the click that started a rebind is not captured
+fn capture_binding(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut controls: ResMut<Controls>,
+    mut message: Query<&mut Text, With<ControlsMessage>>,
+    locale: Res<Locale>,
+) {
+    let Some((action, slot)) = rebinding.0 else {
+        return;
+    };
+    let mut message = message.single_mut();
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        message.sections[0].value.clear();
+        return;
+    }
+
+    let binding = match slot {
+        BindingSlot::Keyboard => keyboard_input
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| {
+                mouse_input
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Mouse(*button))
+            }),
+        BindingSlot::Gamepad => gamepad_input
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::Gamepad(button.button_type)),
+    };
+    let Some(binding) = binding else {
+        return;
+    };
+    rebinding.0 = None;
+
+    if RESERVED_BINDINGS.contains(&binding) {
+        message.sections[0].value = locale.format("controls.reserved", &[("binding", &binding)]);
+        return;
+    }
+
+    let conflict = controls
+        .slot(slot)
+        .iter()
+        .find(|(other, other_binding)| **other != action && **other_binding == binding)
+        .map(|(other, _)| *other);
+
+    if let Some(other) = conflict {
+        message.sections[0].value = locale.format(
+            "controls.conflict",
+            &[("binding", &binding), ("action", &locale.get(other.key()))],
+        );
+        return;
+    }
+
+    controls.slot_mut(slot).insert(action, binding);
+    storage::save(CONTROLS_FILE, &*controls);
+    message.sections[0].value.clear();
+}
+
+fn update_binding_labels(
+    controls: Res<Controls>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&RebindButton, &Children)>,
+    new_buttons: Query<(), Added<RebindButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !controls.is_changed() && !rebinding.is_changed() && new_buttons.is_empty() {
+        return;
+    }
+
+    for (button, children) in buttons.iter() {
+        let Ok(mut text) = texts.get_mut(children[0]) else {
+            continue;
+        };
+
+        text.sections[0].value = if rebinding.0 == Some((button.action, button.slot)) {
+            "...".to_string()
+        } else {
+            controls
+                .slot(button.slot)
+                .get(&button.action)
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "-".to_string())
+        };
+    }
+}
+
+fn close_controls(
+    keyboard_input: Res<Input<KeyCode>>,
+    rebinding: Res<Rebinding>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    // Escape cancels the rebind first, `capture_binding` already cleared it this frame
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        && rebinding.0.is_none()
+        && !rebinding.is_changed()
+    {
+        // Back to the settings screen the controls were opened from
+        overlay.set(Overlay::Settings);
+    }
+}
+
+fn cleanup_controls(
+    mut commands: Commands,
+    query: Query<Entity, With<ControlsScreen>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    rebinding.0 = None;
+}
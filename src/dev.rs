@@ -0,0 +1,355 @@
+use bevy::{input::InputSystem, prelude::*, window::ReceivedCharacter};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    assets::Graphics,
+    camera::TimeScale,
+    collision::CollisionSize,
+    combat::Invulnerable,
+    state::GameState,
+    stats::RunStats,
+    survivour::{Bullet, Downed, MouseWorldCoords, Survivour, SurvivourActions, REVIVE_RADIUS},
+    waves::{Score, Wave, ZombieCount},
+    zombies::{Zombie, ZombieBundle},
+};
+
+// Only built with the `dev` feature, for balancing and reproducing bugs
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleKeys>()
+            .init_resource::<DebugGizmos>()
+            .add_systems(PreUpdate, capture_keys.after(InputSystem))
+            .add_systems(Update, (toggle_console, toggle_gizmos))
+            .add_systems(
+                Update,
+                (type_command, run_command, update_console)
+                    .chain()
+                    .after(toggle_console)
+                    .run_if(console_open),
+            );
+
+        app.add_systems(
+            Update,
+            (draw_collision_boxes, draw_aggro, draw_bullet_paths)
+                .run_if(in_state(GameState::Playing).and_then(gizmos_enabled)),
+        );
+    }
+}
+
+const MAX_LOG_LINES: usize = 12;
+const HELP: &[&str] = &[
+    "spawn <chaser|crawler|bloater> [count] - spawn zombies at the cursor",
+    "wave <number> - set the current wave",
+    "god - toggle god mode",
+    "killall - remove every zombie",
+    "timescale <scale> - change the game speed",
+    "stats - show the run stats",
+];
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("{}", line);
+        self.log.push(line);
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+}
+
+// Keys pressed this frame, read by the console instead of `Input<KeyCode>`
+#[derive(Resource, Default)]
+struct ConsoleKeys(Vec<KeyCode>);
+
+impl ConsoleKeys {
+    fn just_pressed(&self, key: KeyCode) -> bool {
+        self.0.contains(&key)
+    }
+}
+
+#[derive(Resource, Default)]
+struct DebugGizmos(bool);
+
+#[derive(Component)]
+struct ConsoleUi;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn console_open(console: Res<Console>) -> bool {
+    console.open
+}
+
+fn gizmos_enabled(gizmos: Res<DebugGizmos>) -> bool {
+    gizmos.0
+}
+
+// While the console is open the rest of the game doesn't see the keyboard,
+// so typing doesn't pause the game or press menu buttons
+fn capture_keys(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut keys: ResMut<ConsoleKeys>,
+    console: Res<Console>,
+) {
+    keys.0 = keyboard_input.get_just_pressed().copied().collect();
+    if console.open {
+        keyboard_input.reset_all();
+    }
+}
+
+fn toggle_console(
+    mut cmds: Commands,
+    keys: Res<ConsoleKeys>,
+    mut console: ResMut<Console>,
+    mut survivour_actions: ResMut<ToggleActions<SurvivourActions>>,
+    console_ui: Query<Entity, With<ConsoleUi>>,
+) {
+    if !keys.just_pressed(KeyCode::Grave) {
+        return;
+    }
+
+    console.open = !console.open;
+    // The survivours would walk around while typing
+    survivour_actions.enabled = !console.open;
+
+    if !console.open {
+        for entity in console_ui.iter() {
+            cmds.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    cmds.spawn((
+        ConsoleUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+    ))
+    .with_children(|ui| {
+        ui.spawn((
+            TextBundle::from_section(
+                "",
+                // The default font, the game font is hard to read at this size
+                TextStyle {
+                    font_size: 20.0,
+                    color: Color::rgb(0.8, 1.0, 0.8),
+                    ..default()
+                },
+            ),
+            ConsoleText,
+        ));
+    });
+}
+
+fn type_command(
+    mut characters: EventReader<ReceivedCharacter>,
+    keys: Res<ConsoleKeys>,
+    mut console: ResMut<Console>,
+) {
+    for event in characters.read() {
+        // The key opening the console isn't part of the command
+        if !event.char.is_control() && event.char != '`' && event.char != '~' {
+            console.input.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+}
+
+fn run_command(
+    mut cmds: Commands,
+    keys: Res<ConsoleKeys>,
+    mut console: ResMut<Console>,
+    game_state: Res<State<GameState>>,
+    mouse: Res<MouseWorldCoords>,
+    graphics: Res<Graphics>,
+    mut wave: Option<ResMut<Wave>>,
+    mut zombie_count: Option<ResMut<ZombieCount>>,
+    mut time_scale: ResMut<TimeScale>,
+    stats: Option<Res<RunStats>>,
+    score: Option<Res<Score>>,
+    survivours: Query<(Entity, Option<&Invulnerable>), With<Survivour>>,
+    zombies: Query<Entity, With<Zombie>>,
+) {
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+    let input = std::mem::take(&mut console.input);
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let Some(&command) = args.first() else {
+        return;
+    };
+    console.print(format!("> {}", input));
+
+    let in_run = *game_state.get() == GameState::Playing;
+    if !in_run && !matches!(command, "help" | "timescale") {
+        console.print("Only available during a run");
+        return;
+    }
+
+    match (command, &args[1..]) {
+        ("help", _) => {
+            for line in HELP {
+                console.print(*line);
+            }
+        }
+        ("spawn", [kind, rest @ ..]) => {
+            let count = rest.first().and_then(|n| n.parse().ok()).unwrap_or(1);
+            let Some(zombie_count) = zombie_count.as_mut() else {
+                return;
+            };
+            for index in 0..count {
+                // Spread them a bit so they don't stack on one point
+                let position = mouse.0 + Vec2::new(index as f32 * 20.0, 0.0);
+                let bundle = match *kind {
+                    "chaser" => ZombieBundle::chaser(position, &graphics),
+                    "crawler" => ZombieBundle::crawler(position, &graphics),
+                    "bloater" => ZombieBundle::bloater(position, &graphics),
+                    _ => {
+                        console.print(format!("Unknown zombie type {}", kind));
+                        return;
+                    }
+                };
+                zombie_count.increase_count(&bundle.zombie);
+                cmds.spawn(bundle);
+            }
+            console.print(format!("Spawned {} {}", count, kind));
+        }
+        ("wave", [number]) => match (number.parse(), wave.as_mut()) {
+            (Ok(number), Some(wave)) => {
+                wave.count = number;
+                console.print(format!("Wave set to {}", number));
+            }
+            _ => console.print("Usage: wave <number>"),
+        },
+        ("god", _) => {
+            let enable = survivours.iter().any(|(_, god)| god.is_none());
+            for (entity, _) in survivours.iter() {
+                if enable {
+                    cmds.entity(entity).insert(Invulnerable);
+                } else {
+                    cmds.entity(entity).remove::<Invulnerable>();
+                }
+            }
+            console.print(format!("God mode {}", if enable { "on" } else { "off" }));
+        }
+        ("killall", _) => {
+            for entity in zombies.iter() {
+                cmds.entity(entity).despawn();
+            }
+            if let Some(zombie_count) = zombie_count.as_mut() {
+                **zombie_count = ZombieCount::ZERO;
+            }
+            console.print("Killed every zombie");
+        }
+        ("timescale", [scale]) => match scale.parse::<f32>() {
+            Ok(scale) if scale > 0.0 => {
+                time_scale.0 = scale;
+                console.print(format!("Time scale set to {}", scale));
+            }
+            _ => console.print("Usage: timescale <scale above 0>"),
+        },
+        ("stats", _) => {
+            if let (Some(stats), Some(score), Some(wave), Some(zombie_count)) =
+                (stats, score, wave, zombie_count)
+            {
+                console.print(format!(
+                    "Score {}, wave {}, {} zombies left",
+                    score.0,
+                    wave.count,
+                    zombie_count.total()
+                ));
+                console.print(format!(
+                    "{:.0}s survived, {} kills, {}/{} shots hit, {} damage taken, {} pickups",
+                    stats.time_survived.as_secs_f32(),
+                    stats.kills.total(),
+                    stats.shots_hit,
+                    stats.shots_fired,
+                    stats.damage_taken,
+                    stats.pickups_collected
+                ));
+            }
+        }
+        _ => console.print("Unknown command, type help for the list"),
+    }
+}
+
+fn update_console(console: Res<Console>, mut text: Query<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+
+    let mut lines = console.log.clone();
+    lines.push(format!("> {}_", console.input));
+    for mut text in text.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn toggle_gizmos(keyboard_input: Res<Input<KeyCode>>, mut gizmos: ResMut<DebugGizmos>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        gizmos.0 = !gizmos.0;
+    }
+}
+
+fn draw_collision_boxes(mut gizmos: Gizmos, colliders: Query<(&Transform, &CollisionSize)>) {
+    for (tf, size) in colliders.iter() {
+        gizmos.rect_2d(tf.translation.truncate(), 0.0, size.0, Color::GREEN);
+    }
+}
+
+// Zombies go after the closest survivour standing, downed ones can be revived from this close
+fn draw_aggro(
+    mut gizmos: Gizmos,
+    survivours: Query<(&Transform, Option<&Downed>), With<Survivour>>,
+    zombies: Query<&Transform, With<Zombie>>,
+) {
+    for (tf, downed) in survivours.iter() {
+        if downed.is_some() {
+            gizmos.circle_2d(tf.translation.truncate(), REVIVE_RADIUS, Color::CYAN);
+        }
+    }
+
+    for zombie_tf in zombies.iter() {
+        let zombie_pos = zombie_tf.translation.truncate();
+        let target = survivours
+            .iter()
+            .filter(|(_, downed)| downed.is_none())
+            .map(|(tf, _)| tf.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(zombie_pos)
+                    .total_cmp(&b.distance_squared(zombie_pos))
+            });
+        if let Some(target) = target {
+            gizmos.line_2d(zombie_pos, target, Color::rgba(1.0, 0.2, 0.2, 0.4));
+        }
+    }
+}
+
+fn draw_bullet_paths(mut gizmos: Gizmos, bullets: Query<(&Transform, &Bullet)>) {
+    for (tf, bullet) in bullets.iter() {
+        let position = tf.translation.truncate();
+        let direction = tf.rotation.mul_vec3(Vec3::X).truncate();
+        gizmos.line_2d(bullet.start_position, position, Color::YELLOW);
+        gizmos.line_2d(position, position + direction * 200.0, Color::ORANGE);
+    }
+}
@@ -1,26 +1,40 @@
 use bevy::{
     prelude::*,
-    window::{close_on_esc, EnabledButtons, PresentMode},
+    window::{close_on_esc, PresentMode, PrimaryWindow, WindowResized},
     winit::WinitWindows,
 };
 use winit::window::Icon;
 
+use crate::{
+    settings::{Settings, VideoSettings, WindowModeSetting},
+    state::{GameState, Overlay},
+};
+
 pub struct GameConfPlugin;
 
+// The game is designed for this resolution, bigger windows are scaled to fit it
+pub const VIRTUAL_RESOLUTION: Vec2 = Vec2::new(1000.0, 800.0);
+// Read straight from the disk at startup, without waiting for the asset server
+const ICON_PATH: &str = "assets/icon/icon.ico";
+
 impl Plugin for GameConfPlugin {
     fn build(&self, app: &mut App) {
+        // `SettingsPlugin` is added before this one and has loaded the saved settings
+        let video = app.world.resource::<Settings>().video.clone();
+
         app.add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Zombie Arena".into(),
-                        resolution: (1000.0, 800.0).into(),
-                        enabled_buttons: EnabledButtons {
-                            maximize: false,
+                        resolution: (video.resolution.x, video.resolution.y).into(),
+                        mode: video.window_mode.window_mode(),
+                        resize_constraints: WindowResizeConstraints {
+                            min_width: 640.0,
+                            min_height: 480.0,
                             ..default()
                         },
-                        resizable: false,
-                        present_mode: PresentMode::AutoVsync,
+                        present_mode: present_mode(&video),
                         ..default()
                     }),
                     ..default()
@@ -28,8 +42,17 @@ impl Plugin for GameConfPlugin {
                 .set(ImagePlugin::default_nearest()),
         );
 
-        app.add_systems(Startup, set_window_icon)
-            .add_systems(Update, close_on_esc);
+        app.add_systems(Startup, set_window_icon).add_systems(
+            Update,
+            (
+                // Escape pauses during a run, it only quits from the main menu
+                close_on_esc
+                    .run_if(in_state(GameState::MainMenu).and_then(in_state(Overlay::None))),
+                toggle_fullscreen,
+                apply_video_settings.run_if(resource_changed::<Settings>()),
+                scale_ui.run_if(on_event::<WindowResized>()),
+            ),
+        );
     }
 }
 
@@ -37,17 +60,79 @@ fn set_window_icon(
     // we have to use `NonSend` here
     windows: NonSend<WinitWindows>,
 ) {
-    let (icon_rgba, icon_width, icon_height) = {
-        let image = image::open("assets/icon/icon.ico")
-            .expect("Failed to open icon path")
-            .into_rgba8();
-        let (width, height) = image.dimensions();
-        let rgba = image.into_raw();
-        (rgba, width, height)
+    // The game runs fine without its icon, so a missing one isn't fatal
+    let image = match image::open(ICON_PATH) {
+        Ok(image) => image.into_rgba8(),
+        Err(err) => {
+            warn!("Failed to open the window icon {}: {}", ICON_PATH, err);
+            return;
+        }
+    };
+    let (width, height) = image.dimensions();
+    let icon = match Icon::from_rgba(image.into_raw(), width, height) {
+        Ok(icon) => icon,
+        Err(err) => {
+            warn!("Invalid window icon {}: {}", ICON_PATH, err);
+            return;
+        }
     };
-    let icon = Icon::from_rgba(icon_rgba, icon_width, icon_height).unwrap();
 
     for window in windows.windows.values() {
         window.set_window_icon(Some(icon.clone()));
     }
 }
+
+fn present_mode(video: &VideoSettings) -> PresentMode {
+    if video.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    }
+}
+
+fn toggle_fullscreen(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    let alt = keyboard_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if keyboard_input.just_pressed(KeyCode::F11)
+        || (alt && keyboard_input.just_pressed(KeyCode::Return))
+    {
+        settings.video.window_mode = match settings.video.window_mode {
+            WindowModeSetting::Windowed => WindowModeSetting::Borderless,
+            _ => WindowModeSetting::Windowed,
+        };
+    }
+}
+
+fn apply_video_settings(
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    mut resolution: Local<Option<Vec2>>,
+) {
+    let mut window = window.single_mut();
+    let video = &settings.video;
+
+    // Avoid touching the window when another setting changed
+    let mode = video.window_mode.window_mode();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let present_mode = present_mode(video);
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+
+    // Only resize when the setting itself changed, to keep the size the player dragged to.
+    // The window already opened with the saved resolution
+    if resolution.is_some_and(|resolution| resolution != video.resolution) {
+        window
+            .resolution
+            .set(video.resolution.x, video.resolution.y);
+    }
+    *resolution = Some(video.resolution);
+}
+
+// Scale the UI like the world, so the HUD keeps its proportions at any window size
+fn scale_ui(window: Query<&Window, With<PrimaryWindow>>, mut ui_scale: ResMut<UiScale>) {
+    let window = window.single();
+    let scale = (window.width() / VIRTUAL_RESOLUTION.x).min(window.height() / VIRTUAL_RESOLUTION.y);
+    ui_scale.0 = scale as f64;
+}
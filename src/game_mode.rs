@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>();
+    }
+}
+
+#[derive(
+    Resource,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+pub enum GameMode {
+    #[default]
+    Solo,
+    // Two survivours on the same screen, keyboard and mouse plus a gamepad
+    Coop,
+}
+
+impl GameMode {
+    pub fn player_count(&self) -> usize {
+        match self {
+            GameMode::Solo => 1,
+            GameMode::Coop => 2,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            GameMode::Solo => GameMode::Coop,
+            GameMode::Coop => GameMode::Solo,
+        }
+    }
+
+    // Translation key of the name shown in menus
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::Solo => "game-mode.solo",
+            GameMode::Coop => "game-mode.coop",
+        }
+    }
+}
+
+// Picked in the settings, each difficulty has its own high scores
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // Scales the number of zombies in every wave
+    pub fn zombie_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    // Translation key of the name shown in menus
+    pub fn key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::Fonts,
+    game_mode::{Difficulty, GameMode},
+    locale::{Locale, LocalizedText},
+    settings::Settings,
+    state::{GameState, Overlay},
+    storage,
+    ui::spawn_info_screen,
+    waves::{RunSetup, Score, Wave},
+};
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<NameEntry>()
+            .add_systems(OnEnter(GameState::GameOver), check_high_score)
+            .add_systems(OnEnter(Overlay::HighScores), high_scores_ui);
+
+        app.add_systems(OnEnter(Overlay::NameEntry), name_entry_ui)
+            .add_systems(
+                Update,
+                (type_name, confirm_name)
+                    .chain()
+                    .run_if(in_state(Overlay::NameEntry)),
+            )
+            .add_systems(OnExit(Overlay::NameEntry), cleanup_name_entry);
+    }
+}
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+// Bump when the layout of the file changes, older files are then ignored
+const HIGH_SCORES_VERSION: u32 = 1;
+const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LENGTH: usize = 12;
+const DEFAULT_NAME: &str = "Survivour";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: i32,
+    pub wave: i32,
+    // Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub seed: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HighScoreTable {
+    game_mode: GameMode,
+    difficulty: Difficulty,
+    entries: Vec<HighScoreEntry>,
+}
+
+// Best runs for every game mode and difficulty, saved to disk
+#[derive(Resource, Serialize, Deserialize)]
+pub struct HighScores {
+    version: u32,
+    tables: Vec<HighScoreTable>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        Self {
+            version: HIGH_SCORES_VERSION,
+            tables: Vec::new(),
+        }
+    }
+}
+
+impl HighScores {
+    // A missing or corrupt file starts a new table instead of crashing
+    fn load() -> Self {
+        Self::checked(storage::load::<HighScores>(HIGH_SCORES_FILE))
+    }
+
+    fn checked(high_scores: HighScores) -> Self {
+        if high_scores.version != HIGH_SCORES_VERSION {
+            warn!(
+                "Ignoring high scores saved with version {}, expected {}",
+                high_scores.version, HIGH_SCORES_VERSION
+            );
+            return HighScores::default();
+        }
+        high_scores
+    }
+
+    pub fn entries(&self, game_mode: GameMode, difficulty: Difficulty) -> &[HighScoreEntry] {
+        self.tables
+            .iter()
+            .find(|table| table.game_mode == game_mode && table.difficulty == difficulty)
+            .map(|table| table.entries.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn qualifies(&self, game_mode: GameMode, difficulty: Difficulty, score: i32) -> bool {
+        let entries = self.entries(game_mode, difficulty);
+        score > 0
+            && (entries.len() < MAX_ENTRIES
+                || entries.last().is_some_and(|last| score > last.score))
+    }
+
+    fn insert(&mut self, game_mode: GameMode, difficulty: Difficulty, entry: HighScoreEntry) {
+        let index = match self
+            .tables
+            .iter()
+            .position(|table| table.game_mode == game_mode && table.difficulty == difficulty)
+        {
+            Some(index) => index,
+            None => {
+                self.tables.push(HighScoreTable {
+                    game_mode,
+                    difficulty,
+                    entries: Vec::new(),
+                });
+                self.tables.len() - 1
+            }
+        };
+
+        let entries = &mut self.tables[index].entries;
+        entries.push(entry);
+        // Stable sort, older runs stay first on a tie
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(MAX_ENTRIES);
+    }
+}
+
+// The run waiting for a name before it's added to the high scores
+#[derive(Resource, Default)]
+struct NameEntry {
+    // Kept between runs, so the same player doesn't have to type it again
+    name: String,
+    pending: Option<(GameMode, Difficulty, HighScoreEntry)>,
+}
+
+fn check_high_score(
+    high_scores: Res<HighScores>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    run: Res<RunSetup>,
+    game_mode: Res<GameMode>,
+    mut name_entry: ResMut<NameEntry>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    if !high_scores.qualifies(*game_mode, run.difficulty, score.0) {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    name_entry.pending = Some((
+        *game_mode,
+        run.difficulty,
+        HighScoreEntry {
+            name: String::new(),
+            score: score.0,
+            wave: wave.count,
+            timestamp,
+            seed: run.seed,
+        },
+    ));
+    overlay.set(Overlay::NameEntry);
+}
+
+// Turns a Unix timestamp into a year-month-day date, without pulling a date crate
+fn format_date(timestamp: u64) -> String {
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{}-{:02}-{:02}", year, month, day)
+}
+
+fn high_scores_ui(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    high_scores: Res<HighScores>,
+    game_mode: Res<GameMode>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    let difficulty = settings.gameplay.difficulty;
+    let entries = high_scores.entries(*game_mode, difficulty);
+
+    let mut lines = vec![locale.format(
+        "high-scores.table",
+        &[
+            ("mode", &locale.get(game_mode.key())),
+            ("difficulty", &locale.get(difficulty.key())),
+        ],
+    )];
+    if entries.is_empty() {
+        lines.push(locale.get("high-scores.empty"));
+    }
+    for (rank, entry) in entries.iter().enumerate() {
+        lines.push(locale.plural(
+            "high-scores.entry",
+            entry.score as i64,
+            &[
+                ("rank", &(rank + 1)),
+                ("name", &entry.name),
+                ("wave", &entry.wave),
+                ("date", &format_date(entry.timestamp)),
+            ],
+        ));
+    }
+
+    spawn_info_screen(
+        &mut commands,
+        &fonts,
+        &locale.get("high-scores.title"),
+        &lines,
+    );
+}
+
+// All the stuff related to the name entry
+#[derive(Component)]
+struct NameEntryScreen;
+
+#[derive(Component)]
+struct NameText;
+
+fn name_entry_ui(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    name_entry: Res<NameEntry>,
+    mut characters: ResMut<Events<ReceivedCharacter>>,
+) {
+    // Keys pressed during the run are not part of the name
+    characters.clear();
+
+    let text_style = |font_size: f32| TextStyle {
+        font: fonts.zombiecontrol.clone(),
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn((
+            NameEntryScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section("", text_style(72.0)),
+                LocalizedText::new("name-entry.title"),
+            ));
+            ui.spawn((
+                TextBundle::from_section("", text_style(32.0)),
+                LocalizedText::new("name-entry.prompt"),
+            ));
+            ui.spawn((
+                TextBundle::from_section(format!("{}_", name_entry.name), text_style(48.0)),
+                NameText,
+            ));
+            ui.spawn((
+                TextBundle::from_section("", text_style(24.0)),
+                LocalizedText::new("name-entry.save"),
+            ));
+        });
+}
+
+fn type_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut name_entry: ResMut<NameEntry>,
+    mut name_text: Query<&mut Text, With<NameText>>,
+) {
+    for event in characters.read() {
+        if !event.char.is_control() && name_entry.name.chars().count() < MAX_NAME_LENGTH {
+            name_entry.name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        name_entry.name.pop();
+    }
+
+    if name_entry.is_changed() {
+        for mut text in name_text.iter_mut() {
+            text.sections[0].value = format!("{}_", name_entry.name);
+        }
+    }
+}
+
+fn confirm_name(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    // Gamepad players can't type, they get the default name
+    let gamepad_confirm = gamepad_buttons
+        .get_just_pressed()
+        .any(|button| button.button_type == GamepadButtonType::South);
+    if !keyboard_input.just_pressed(KeyCode::Return) && !gamepad_confirm {
+        return;
+    }
+
+    let name = match name_entry.name.trim() {
+        "" => DEFAULT_NAME.to_string(),
+        name => name.to_string(),
+    };
+    if let Some((game_mode, difficulty, mut entry)) = name_entry.pending.take() {
+        entry.name = name;
+        high_scores.insert(game_mode, difficulty, entry);
+        storage::save(HIGH_SCORES_FILE, &*high_scores);
+    }
+    overlay.set(Overlay::None);
+}
+
+fn cleanup_name_entry(mut commands: Commands, query: Query<Entity, With<NameEntryScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: i32) -> HighScoreEntry {
+        HighScoreEntry {
+            name: name.to_string(),
+            score,
+            wave: 1,
+            timestamp: 0,
+            seed: 0,
+        }
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<i32> {
+        high_scores
+            .entries(GameMode::Solo, Difficulty::Normal)
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn other_versions_are_ignored() {
+        let saved: HighScores = ron::from_str(
+            "(version: 0, tables: [(game_mode: Solo, difficulty: Normal, entries: [\
+             (name: \"Old\", score: 100, wave: 3, timestamp: 0, seed: 0)])])",
+        )
+        .unwrap();
+
+        let high_scores = HighScores::checked(saved);
+        assert_eq!(high_scores.version, HIGH_SCORES_VERSION);
+        assert!(high_scores.tables.is_empty());
+    }
+
+    #[test]
+    fn current_version_is_kept() {
+        let mut saved = HighScores::default();
+        saved.insert(GameMode::Solo, Difficulty::Normal, entry("Ann", 100));
+
+        assert_eq!(scores(&HighScores::checked(saved)), vec![100]);
+    }
+
+    #[test]
+    fn insert_keeps_the_best_scores_first() {
+        let mut high_scores = HighScores::default();
+        for (name, score) in [("A", 50), ("B", 200), ("C", 100), ("D", 100)] {
+            high_scores.insert(GameMode::Solo, Difficulty::Normal, entry(name, score));
+        }
+
+        assert_eq!(scores(&high_scores), vec![200, 100, 100, 50]);
+        // The older run stays first on a tie
+        let entries = high_scores.entries(GameMode::Solo, Difficulty::Normal);
+        assert_eq!(entries[1].name, "C");
+        assert_eq!(entries[2].name, "D");
+    }
+
+    #[test]
+    fn tables_are_kept_per_mode_and_difficulty() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(GameMode::Coop, Difficulty::Hard, entry("A", 100));
+
+        assert!(high_scores
+            .entries(GameMode::Solo, Difficulty::Normal)
+            .is_empty());
+        assert_eq!(
+            high_scores.entries(GameMode::Coop, Difficulty::Hard).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn table_is_capped() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_ENTRIES as i32 + 5 {
+            high_scores.insert(GameMode::Solo, Difficulty::Normal, entry("A", score));
+        }
+
+        let scores = scores(&high_scores);
+        assert_eq!(scores.len(), MAX_ENTRIES);
+        assert_eq!(scores.first(), Some(&(MAX_ENTRIES as i32 + 5)));
+        assert_eq!(scores.last(), Some(&6));
+    }
+
+    #[test]
+    fn qualifies_only_above_the_last_full_entry() {
+        let mut high_scores = HighScores::default();
+        assert!(!high_scores.qualifies(GameMode::Solo, Difficulty::Normal, 0));
+        assert!(high_scores.qualifies(GameMode::Solo, Difficulty::Normal, 1));
+
+        for score in 1..=MAX_ENTRIES as i32 {
+            high_scores.insert(GameMode::Solo, Difficulty::Normal, entry("A", score * 10));
+        }
+        assert!(!high_scores.qualifies(GameMode::Solo, Difficulty::Normal, 10));
+        assert!(high_scores.qualifies(GameMode::Solo, Difficulty::Normal, 11));
+        // Other tables are still empty
+        assert!(high_scores.qualifies(GameMode::Coop, Difficulty::Normal, 1));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_709_251_199), "2024-02-29");
+        assert_eq!(format_date(1_735_689_599), "2024-12-31");
+    }
+}
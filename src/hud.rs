@@ -0,0 +1,434 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::{Fonts, Graphics},
+    combat::{Health, MaxHealth},
+    game_mode::GameMode,
+    locale::{Locale, LocalizedText},
+    movement::Stamina,
+    settings::Settings,
+    state::{gameplay_running, GameState},
+    survivour::{DodgeCooldown, Downed, Player, Survivour},
+    waves::{Score, Wave, ZombieCount},
+    zombies::Zombie,
+};
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_hud)
+            .add_systems(OnExit(GameState::Playing), cleanup_hud);
+        app.add_systems(
+            Update,
+            (
+                update_wave_text,
+                update_score,
+                update_zombies_remaining,
+                update_hearts,
+                update_status_text,
+                update_stamina_bar,
+                update_dodge_bar,
+            )
+                .run_if(gameplay_running),
+        )
+        // Also while paused, the settings can be changed from the pause menu
+        .add_systems(
+            Update,
+            apply_hud_contrast.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+// Root of every HUD node, despawned when the run ends
+#[derive(Component)]
+pub struct Hud;
+
+#[derive(Component)]
+pub struct ScoreText;
+
+#[derive(Component)]
+pub struct WaveText;
+
+// Remaining zombies of one kind
+#[derive(Component)]
+pub struct ZombieLeftText(pub Zombie);
+
+// The value is the index of the player the node belongs to
+#[derive(Component)]
+pub struct HeartRow(pub usize);
+
+#[derive(Component)]
+pub struct StatusText(pub usize);
+
+#[derive(Component)]
+pub struct StaminaBar(pub usize);
+
+#[derive(Component)]
+pub struct DodgeBar(pub usize);
+
+// Groups of HUD elements that get a solid background in high contrast mode
+#[derive(Component)]
+struct HudPanel;
+
+const STAMINA_COLOR: Color = Color::rgb(0.9, 0.8, 0.2);
+const EXHAUSTED_COLOR: Color = Color::rgb(0.6, 0.3, 0.2);
+const DODGE_READY_COLOR: Color = Color::rgb(0.3, 0.7, 0.9);
+const DODGE_COOLDOWN_COLOR: Color = Color::rgb(0.3, 0.4, 0.5);
+const FULL_HEART_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+const EMPTY_HEART_COLOR: Color = Color::rgba(0.3, 0.3, 0.3, 0.6);
+const ZOMBIES: [Zombie; 3] = [Zombie::Chaser, Zombie::Crawler, Zombie::Bloater];
+const CONTRAST_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+const CONTRAST_BORDER: Color = Color::WHITE;
+
+fn spawn_hud(
+    mut cmds: Commands,
+    fonts: Res<Fonts>,
+    graphics: Res<Graphics>,
+    game_mode: Res<GameMode>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: fonts.zombiecontrol.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+    // Room for the high contrast background and border, invisible otherwise
+    let panel_style = Style {
+        padding: UiRect::all(Val::Px(6.0)),
+        border: UiRect::all(Val::Px(2.0)),
+        ..default()
+    };
+
+    // Wave, score and zombies left along the top of the screen
+    cmds.spawn((
+        Hud,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::FlexStart,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_sections([
+                TextSection::new("", text_style(40.0)),
+                TextSection::new("", text_style(24.0)),
+            ])
+            .with_style(panel_style.clone()),
+            BorderColor(Color::NONE),
+            HudPanel,
+            WaveText,
+        ));
+        parent.spawn((
+            TextBundle::from_section("", text_style(40.0)).with_style(panel_style.clone()),
+            BorderColor(Color::NONE),
+            HudPanel,
+            ScoreText,
+        ));
+        parent
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(8.0),
+                        ..panel_style.clone()
+                    },
+                    ..default()
+                },
+                HudPanel,
+            ))
+            .with_children(|zombies| {
+                for zombie in ZOMBIES {
+                    let texture = match zombie {
+                        Zombie::Chaser => graphics.chaser.clone(),
+                        Zombie::Crawler => graphics.crawler.clone(),
+                        Zombie::Bloater => graphics.bloater.clone(),
+                    };
+                    zombies.spawn(ImageBundle {
+                        style: Style {
+                            width: Val::Px(32.0),
+                            height: Val::Px(32.0),
+                            ..default()
+                        },
+                        image: texture.into(),
+                        ..default()
+                    });
+                    zombies.spawn((
+                        TextBundle::from_section("0", text_style(32.0)),
+                        ZombieLeftText(zombie),
+                    ));
+                }
+            });
+    });
+
+    // One panel per player, player one on the left and player two on the right
+    cmds.spawn((
+        Hud,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(0.0),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::FlexEnd,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        for player in 0..game_mode.player_count() {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: if player == 0 {
+                                AlignItems::FlexStart
+                            } else {
+                                AlignItems::FlexEnd
+                            },
+                            row_gap: Val::Px(6.0),
+                            ..panel_style.clone()
+                        },
+                        ..default()
+                    },
+                    HudPanel,
+                ))
+                .with_children(|panel| {
+                    if *game_mode == GameMode::Coop {
+                        panel.spawn((
+                            TextBundle::from_section("", text_style(24.0)),
+                            LocalizedText::new("hud.player").with_arg("number", player + 1),
+                        ));
+                    }
+                    panel.spawn((
+                        NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(4.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        HeartRow(player),
+                    ));
+                    panel.spawn((
+                        TextBundle::from_section("", text_style(24.0)),
+                        StatusText(player),
+                    ));
+                    spawn_hud_bar(panel, 12.0, STAMINA_COLOR, StaminaBar(player));
+                    spawn_hud_bar(panel, 6.0, DODGE_READY_COLOR, DodgeBar(player));
+                });
+        }
+    });
+}
+
+fn spawn_hud_bar(parent: &mut ChildBuilder, height: f32, color: Color, marker: impl Component) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(300.0),
+                height: Val::Px(height),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            ..default()
+        })
+        .with_children(|bar| {
+            bar.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                },
+                marker,
+            ));
+        });
+}
+
+fn apply_hud_contrast(
+    settings: Res<Settings>,
+    mut panels: Query<(Ref<HudPanel>, &mut BackgroundColor, &mut BorderColor)>,
+) {
+    let (background, border) = if settings.accessibility.high_contrast {
+        (CONTRAST_BACKGROUND, CONTRAST_BORDER)
+    } else {
+        (Color::NONE, Color::NONE)
+    };
+
+    for (panel, mut background_color, mut border_color) in panels.iter_mut() {
+        if settings.is_changed() || panel.is_added() {
+            background_color.0 = background;
+            border_color.0 = border;
+        }
+    }
+}
+
+fn update_wave_text(
+    wave: Res<Wave>,
+    zombies: Res<ZombieCount>,
+    mut wave_text: Query<&mut Text, With<WaveText>>,
+    locale: Res<Locale>,
+) {
+    // The timer only runs between waves, once every zombie is dead
+    let countdown = if zombies.total() == 0 && !wave.timer.finished() {
+        let seconds = wave.timer.remaining_secs().ceil();
+        format!(
+            "  {}",
+            locale.format("hud.next-wave", &[("seconds", &seconds)])
+        )
+    } else {
+        String::new()
+    };
+
+    for mut text in wave_text.iter_mut() {
+        text.sections[0].value = locale.format("hud.wave", &[("wave", &wave.count)]);
+        text.sections[1].value = countdown.clone();
+    }
+}
+
+fn update_zombies_remaining(
+    zombies: Res<ZombieCount>,
+    mut zombie_left_text: Query<(&mut Text, &ZombieLeftText)>,
+) {
+    for (mut text, zombie) in zombie_left_text.iter_mut() {
+        text.sections[0].value = zombies.count(zombie.0).to_string();
+    }
+}
+
+fn update_score(
+    score: Res<Score>,
+    mut score_text: Query<&mut Text, With<ScoreText>>,
+    locale: Res<Locale>,
+) {
+    for mut text in score_text.iter_mut() {
+        text.sections[0].value = locale.format("hud.score", &[("score", &score.0)]);
+    }
+}
+
+fn update_hearts(
+    mut cmds: Commands,
+    survivours: Query<(&Health, &MaxHealth, &Player), With<Survivour>>,
+    heart_rows: Query<(Entity, &HeartRow, Option<&Children>)>,
+    mut hearts: Query<&mut BackgroundColor>,
+    graphics: Res<Graphics>,
+) {
+    for (row, heart_row, children) in heart_rows.iter() {
+        let Some((health, max_health, _)) = survivours
+            .iter()
+            .find(|(_, _, player)| player.0 == heart_row.0)
+        else {
+            continue;
+        };
+
+        // Rebuild the row when the max health changes, otherwise only tint the hearts
+        let hearts_count = children.map_or(0, |children| children.len());
+        if hearts_count != max_health.0.max(0) as usize {
+            cmds.entity(row).despawn_descendants().with_children(|row| {
+                for index in 0..max_health.0 {
+                    row.spawn(ImageBundle {
+                        style: Style {
+                            width: Val::Px(28.0),
+                            height: Val::Px(28.0),
+                            ..default()
+                        },
+                        image: graphics.heart.clone().into(),
+                        background_color: heart_color(index, health.0).into(),
+                        ..default()
+                    });
+                }
+            });
+            continue;
+        }
+
+        let Some(children) = children else {
+            continue;
+        };
+        for (index, &heart) in children.iter().enumerate() {
+            if let Ok(mut color) = hearts.get_mut(heart) {
+                *color = heart_color(index as i32, health.0).into();
+            }
+        }
+    }
+}
+
+fn heart_color(index: i32, health: i32) -> Color {
+    if index < health {
+        FULL_HEART_COLOR
+    } else {
+        EMPTY_HEART_COLOR
+    }
+}
+
+fn update_status_text(
+    survivours: Query<(Option<&Downed>, &Player), With<Survivour>>,
+    mut status_text: Query<(&StatusText, &mut Text)>,
+    locale: Res<Locale>,
+) {
+    for (status, mut text) in status_text.iter_mut() {
+        let Some((downed, _)) = survivours.iter().find(|(_, player)| player.0 == status.0) else {
+            continue;
+        };
+
+        text.sections[0].value = match downed {
+            Some(downed) => {
+                let percent = format!("{:.0}", downed.revive.percent() * 100.0);
+                locale.format("hud.downed", &[("percent", &percent)])
+            }
+            None => String::new(),
+        };
+    }
+}
+
+fn update_stamina_bar(
+    stamina: Query<(&Stamina, &Player)>,
+    mut stamina_bar: Query<(&StaminaBar, &mut Style, &mut BackgroundColor)>,
+) {
+    for (bar, mut style, mut color) in stamina_bar.iter_mut() {
+        let Some((stamina, _)) = stamina.iter().find(|(_, player)| player.0 == bar.0) else {
+            continue;
+        };
+
+        style.width = Val::Percent(stamina.fraction() * 100.0);
+        *color = if stamina.exhausted {
+            EXHAUSTED_COLOR.into()
+        } else {
+            STAMINA_COLOR.into()
+        };
+    }
+}
+
+fn update_dodge_bar(
+    cooldown: Query<(&DodgeCooldown, &Player)>,
+    mut dodge_bar: Query<(&DodgeBar, &mut Style, &mut BackgroundColor)>,
+) {
+    for (bar, mut style, mut color) in dodge_bar.iter_mut() {
+        let Some((cooldown, _)) = cooldown.iter().find(|(_, player)| player.0 == bar.0) else {
+            continue;
+        };
+
+        style.width = Val::Percent(cooldown.percent() * 100.0);
+        *color = if cooldown.finished() {
+            DODGE_READY_COLOR.into()
+        } else {
+            DODGE_COOLDOWN_COLOR.into()
+        };
+    }
+}
+
+fn cleanup_hud(mut cmds: Commands, query: Query<Entity, With<Hud>>) {
+    for entity in query.iter() {
+        cmds.entity(entity).despawn_recursive();
+    }
+}
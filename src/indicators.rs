@@ -0,0 +1,221 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    transform::TransformSystem,
+};
+
+use crate::{
+    accessibility::ColorPalette,
+    camera::{CameraFollow, GameCamera},
+    powerups::PowerUp,
+    settings::Settings,
+    state::{gameplay_running, GameState},
+    zombies::Zombie,
+};
+
+pub struct IndicatorsPlugin;
+
+impl Plugin for IndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_indicators)
+            .add_systems(
+                PostUpdate,
+                update_indicators
+                    .after(CameraFollow)
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(gameplay_running),
+            )
+            .add_systems(
+                Update,
+                recolor_indicators.run_if(
+                    resource_exists::<IndicatorMaterials>()
+                        .and_then(resource_changed::<Settings>()),
+                ),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_indicators);
+    }
+}
+
+// Arrow at the edge of the screen, pointing at something outside the view
+#[derive(Component)]
+struct Indicator;
+
+// One material per kind of target, so the arrows are told apart by colour
+#[derive(Resource)]
+struct IndicatorMaterials {
+    chaser: Handle<ColorMaterial>,
+    crawler: Handle<ColorMaterial>,
+    bloater: Handle<ColorMaterial>,
+    health: Handle<ColorMaterial>,
+}
+
+// The closest targets get an arrow, the arrows are reused every frame
+const MAX_ZOMBIE_INDICATORS: usize = 8;
+const MAX_PICKUP_INDICATORS: usize = 3;
+const INDICATOR_SIZE: f32 = 14.0;
+const INDICATOR_Z: f32 = 60.0;
+// Distance between the arrows and the screen edge
+const EDGE_MARGIN: f32 = 24.0;
+// Arrows shrink down to `MIN_SCALE` for targets this far past the screen edge
+const FAR_DISTANCE: f32 = 1200.0;
+const MIN_SCALE: f32 = 0.5;
+const INDICATOR_ALPHA: f32 = 0.8;
+
+impl IndicatorMaterials {
+    fn colors(palette: ColorPalette) -> [Color; 4] {
+        [
+            palette.zombie_color(&Zombie::Chaser),
+            palette.zombie_color(&Zombie::Crawler),
+            palette.zombie_color(&Zombie::Bloater),
+            palette.pickup_color(&PowerUp::Health),
+        ]
+        .map(|color| color.with_a(INDICATOR_ALPHA))
+    }
+
+    fn handles(&self) -> [&Handle<ColorMaterial>; 4] {
+        [&self.chaser, &self.crawler, &self.bloater, &self.health]
+    }
+}
+
+fn spawn_indicators(
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+) {
+    let [chaser, crawler, bloater, health] =
+        IndicatorMaterials::colors(settings.accessibility.palette)
+            .map(|color| materials.add(color.into()));
+    let indicator_materials = IndicatorMaterials {
+        chaser,
+        crawler,
+        bloater,
+        health,
+    };
+    // A triangle pointing up, rotated towards its target
+    let mesh: Mesh2dHandle = meshes
+        .add(shape::RegularPolygon::new(INDICATOR_SIZE, 3).into())
+        .into();
+
+    for _ in 0..MAX_ZOMBIE_INDICATORS + MAX_PICKUP_INDICATORS {
+        cmds.spawn((
+            MaterialMesh2dBundle {
+                mesh: mesh.clone(),
+                material: indicator_materials.chaser.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Indicator,
+        ));
+    }
+    cmds.insert_resource(indicator_materials);
+}
+
+fn update_indicators(
+    camera: Query<(&Transform, &OrthographicProjection), With<GameCamera>>,
+    zombies: Query<(&Transform, &Zombie)>,
+    powerups: Query<(&Transform, &PowerUp)>,
+    mut indicators: Query<
+        (&mut Transform, &mut Visibility, &mut Handle<ColorMaterial>),
+        (
+            With<Indicator>,
+            Without<GameCamera>,
+            Without<Zombie>,
+            Without<PowerUp>,
+        ),
+    >,
+    materials: Res<IndicatorMaterials>,
+) {
+    let Ok((cam_tf, projection)) = camera.get_single() else {
+        return;
+    };
+    let center = cam_tf.translation.truncate();
+    let half_view = projection.area.half_size();
+    let edge = (half_view - EDGE_MARGIN).max(Vec2::ZERO);
+
+    let off_screen = |position: Vec2| {
+        let offset = position - center;
+        offset.x.abs() > half_view.x || offset.y.abs() > half_view.y
+    };
+    let closest = |mut targets: Vec<(Vec2, Handle<ColorMaterial>)>, count: usize| {
+        targets.sort_by(|(a, _), (b, _)| {
+            a.distance_squared(center)
+                .total_cmp(&b.distance_squared(center))
+        });
+        targets.truncate(count);
+        targets
+    };
+
+    let zombie_targets = zombies
+        .iter()
+        .map(|(tf, zombie)| (tf.translation.truncate(), zombie))
+        .filter(|(position, _)| off_screen(*position))
+        .map(|(position, zombie)| {
+            let material = match zombie {
+                Zombie::Chaser => materials.chaser.clone(),
+                Zombie::Crawler => materials.crawler.clone(),
+                Zombie::Bloater => materials.bloater.clone(),
+            };
+            (position, material)
+        })
+        .collect();
+    let pickup_targets = powerups
+        .iter()
+        .map(|(tf, powerup)| (tf.translation.truncate(), powerup))
+        .filter(|(position, _)| off_screen(*position))
+        .map(|(position, powerup)| {
+            let material = match powerup {
+                PowerUp::Health => materials.health.clone(),
+            };
+            (position, material)
+        })
+        .collect();
+
+    let mut targets = closest(zombie_targets, MAX_ZOMBIE_INDICATORS)
+        .into_iter()
+        .chain(closest(pickup_targets, MAX_PICKUP_INDICATORS));
+
+    for (mut transform, mut visibility, mut material) in indicators.iter_mut() {
+        let Some((position, target_material)) = targets.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // Walk from the middle of the screen towards the target until the edge is reached
+        let offset = position - center;
+        let to_edge = (edge / offset.abs()).min_element();
+        let edge_position = center + offset * to_edge;
+
+        let past_edge = (offset.length() * (1.0 - to_edge)).max(0.0);
+        let scale = 1.0 - (past_edge / FAR_DISTANCE).min(1.0) * (1.0 - MIN_SCALE);
+
+        transform.translation = edge_position.extend(INDICATOR_Z);
+        transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x) - FRAC_PI_2);
+        // Keeps the same size on screen whatever the zoom
+        transform.scale = Vec3::splat(scale * projection.scale);
+        *material = target_material;
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn recolor_indicators(
+    settings: Res<Settings>,
+    indicator_materials: Res<IndicatorMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let colors = IndicatorMaterials::colors(settings.accessibility.palette);
+    for (handle, color) in indicator_materials.handles().into_iter().zip(colors) {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = color;
+        }
+    }
+}
+
+fn cleanup_indicators(mut cmds: Commands, indicators: Query<Entity, With<Indicator>>) {
+    for entity in indicators.iter() {
+        cmds.entity(entity).despawn();
+    }
+    cmds.remove_resource::<IndicatorMaterials>();
+}
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+
+use ab_glyph::Font as _;
+use bevy::{prelude::*, text::update_text2d_layout, ui::UiSystem};
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+use crate::{assets::Fonts, settings::Settings};
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        let language = app.world.resource::<Settings>().language;
+
+        let (locale, errors) = Locale::load(language);
+
+        app.insert_resource(locale)
+            .insert_resource(LocaleLoadErrors(errors))
+            .add_systems(Startup, log_load_errors)
+            .add_systems(
+                Update,
+                switch_language.run_if(resource_changed::<Settings>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (localize_texts, fallback_fonts)
+                    .chain()
+                    .before(UiSystem::Layout)
+                    .before(update_text2d_layout),
+            );
+    }
+}
+
+// Translations ship with the game, a missing key falls back to English
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize, EnumIter)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl Language {
+    fn messages(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/locales/en.ron"),
+            Language::French => include_str!("../assets/locales/fr.ron"),
+        }
+    }
+
+    // Shown in the language picker in the language itself
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+
+    // Suffix of the message to use for a count, e.g. `kills.chasers.one`
+    fn plural_category(&self, count: i64) -> &'static str {
+        match self {
+            Language::English if count == 1 => "one",
+            Language::French if count == 0 || count == 1 => "one",
+            _ => "other",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Locale {
+    // Errors are returned, the first locale is read before logging is set up
+    fn load(language: Language) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut read = |language: Language| {
+            ron::from_str(language.messages()).unwrap_or_else(|err| {
+                errors.push(format!(
+                    "Failed to read the {:?} translation: {}",
+                    language, err
+                ));
+                HashMap::new()
+            })
+        };
+
+        let locale = Self {
+            language,
+            messages: read(language),
+            fallback: read(Language::English),
+        };
+        (locale, errors)
+    }
+
+    // The key itself is shown when no translation has it
+    fn message<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.message(key).to_string()
+    }
+
+    // Replaces every `{name}` placeholder with its value
+    pub fn format(&self, key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        args.iter()
+            .fold(self.message(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), &value.to_string())
+            })
+    }
+
+    // Picks the plural form for `count`, which is also available as `{count}`
+    pub fn plural(&self, key: &str, count: i64, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        let key = format!("{}.{}", key, self.language.plural_category(count));
+        let mut args = args.to_vec();
+        args.push(("count", &count));
+        self.format(&key, &args)
+    }
+
+    fn localize(&self, text: &LocalizedText) -> String {
+        let values: Vec<String> = text
+            .args
+            .iter()
+            .map(|(_, arg)| match arg {
+                LocalizedArg::Text(value) => value.clone(),
+                LocalizedArg::Key(key) => self.get(key),
+            })
+            .collect();
+        let args: Vec<(&str, &dyn std::fmt::Display)> = text
+            .args
+            .iter()
+            .zip(&values)
+            .map(|((name, _), value)| (*name, value as &dyn std::fmt::Display))
+            .collect();
+
+        self.format(text.key, &args)
+    }
+}
+
+#[derive(Clone)]
+pub enum LocalizedArg {
+    Text(String),
+    // Translated as well, e.g. the name of a game mode
+    Key(&'static str),
+}
+
+// Sets the first section of the text, and updates it when the language changes
+#[derive(Component, Clone)]
+pub struct LocalizedText {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, LocalizedArg)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args
+            .push((name, LocalizedArg::Text(value.to_string())));
+        self
+    }
+
+    pub fn with_key_arg(mut self, name: &'static str, key: &'static str) -> Self {
+        self.args.push((name, LocalizedArg::Key(key)));
+        self
+    }
+}
+
+impl From<&'static str> for LocalizedText {
+    fn from(key: &'static str) -> Self {
+        Self::new(key)
+    }
+}
+
+#[derive(Resource)]
+struct LocaleLoadErrors(Vec<String>);
+
+fn log_load_errors(errors: Res<LocaleLoadErrors>) {
+    for err in errors.0.iter() {
+        error!("{}", err);
+    }
+}
+
+fn switch_language(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if settings.language != locale.language {
+        let (new_locale, errors) = Locale::load(settings.language);
+        for err in errors {
+            error!("{}", err);
+        }
+        *locale = new_locale;
+    }
+}
+
+fn localize_texts(locale: Res<Locale>, mut texts: Query<(Ref<LocalizedText>, &mut Text)>) {
+    for (localized, mut text) in texts.iter_mut() {
+        if locale.is_changed() || localized.is_changed() {
+            text.sections[0].value = locale.localize(&localized);
+        }
+    }
+}
+
+// Marks texts drawn with the fallback font because the game font lacks some of their glyphs
+#[derive(Component)]
+struct FontFallback;
+
+fn fallback_fonts(
+    mut commands: Commands,
+    mut texts: Query<(Entity, &mut Text, Has<FontFallback>), Changed<Text>>,
+    fonts: Option<Res<Fonts>>,
+    font_assets: Res<Assets<Font>>,
+) {
+    // The fonts are still loading
+    let Some(fonts) = fonts else {
+        return;
+    };
+    let Some(game_font) = font_assets.get(&fonts.zombiecontrol) else {
+        return;
+    };
+    // The default font, bundled with bevy
+    let fallback = Handle::<Font>::default();
+    let supported = |value: &str| {
+        value
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .all(|c| game_font.font.glyph_id(c).0 != 0)
+    };
+
+    for (entity, mut text, has_fallback) in texts.iter_mut() {
+        // Only touch the game font, other texts picked their font on purpose
+        let needs_fallback = text
+            .sections
+            .iter()
+            .any(|section| section.style.font == fonts.zombiecontrol && !supported(&section.value));
+        let can_restore = has_fallback
+            && text
+                .sections
+                .iter()
+                .any(|section| section.style.font == fallback && supported(&section.value));
+        if !needs_fallback && !can_restore {
+            continue;
+        }
+
+        for section in text.sections.iter_mut() {
+            if section.style.font == fonts.zombiecontrol && !supported(&section.value) {
+                section.style.font = fallback.clone();
+            } else if has_fallback && section.style.font == fallback && supported(&section.value) {
+                section.style.font = fonts.zombiecontrol.clone();
+            }
+        }
+        commands.entity(entity).insert(FontFallback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn messages(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn locale(language: Language, entries: &[(&str, &str)]) -> Locale {
+        Locale {
+            language,
+            messages: messages(entries),
+            fallback: messages(&[("only-english", "English text")]),
+        }
+    }
+
+    #[test]
+    fn format_replaces_every_placeholder() {
+        let locale = locale(
+            Language::English,
+            &[("greet", "{name} met {name} on wave {wave}")],
+        );
+
+        assert_eq!(
+            locale.format("greet", &[("name", &"Ann"), ("wave", &3)]),
+            "Ann met Ann on wave 3"
+        );
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_english_then_the_key() {
+        let locale = locale(Language::French, &[]);
+
+        assert_eq!(locale.get("only-english"), "English text");
+        assert_eq!(locale.get("missing"), "missing");
+    }
+
+    #[test]
+    fn english_plural() {
+        let locale = locale(
+            Language::English,
+            &[
+                ("kills.one", "{count} kill"),
+                ("kills.other", "{count} kills"),
+            ],
+        );
+
+        assert_eq!(locale.plural("kills", 0, &[]), "0 kills");
+        assert_eq!(locale.plural("kills", 1, &[]), "1 kill");
+        assert_eq!(locale.plural("kills", 2, &[]), "2 kills");
+    }
+
+    #[test]
+    fn french_uses_the_singular_for_zero() {
+        let locale = locale(
+            Language::French,
+            &[
+                ("kills.one", "{count} mort"),
+                ("kills.other", "{count} morts"),
+            ],
+        );
+
+        assert_eq!(locale.plural("kills", 0, &[]), "0 mort");
+        assert_eq!(locale.plural("kills", 1, &[]), "1 mort");
+        assert_eq!(locale.plural("kills", 2, &[]), "2 morts");
+    }
+
+    #[test]
+    fn plural_keeps_the_other_args() {
+        let locale = locale(
+            Language::English,
+            &[("entry.other", "{name}: {count} points")],
+        );
+
+        assert_eq!(
+            locale.plural("entry", 20, &[("name", &"Ann")]),
+            "Ann: 20 points"
+        );
+    }
+
+    #[test]
+    fn translations_have_the_same_keys() {
+        let (english, errors) = Locale::load(Language::English);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        for language in Language::iter() {
+            let (locale, errors) = Locale::load(language);
+            assert!(errors.is_empty(), "{:?}", errors);
+
+            let mut missing: Vec<_> = english
+                .messages
+                .keys()
+                .filter(|key| !locale.messages.contains_key(*key))
+                .collect();
+            missing.sort();
+            assert!(
+                missing.is_empty(),
+                "{:?} is missing {:?}",
+                language,
+                missing
+            );
+            assert_eq!(locale.messages.len(), english.messages.len());
+        }
+    }
+}
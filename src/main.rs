@@ -4,44 +4,96 @@
 
 use bevy::prelude::*;
 
+mod accessibility;
+mod achievements;
 mod assets;
+mod camera;
 mod collision;
 mod combat;
+mod controls;
+#[cfg(feature = "dev")]
+mod dev;
 mod game_conf;
+mod game_mode;
+mod high_scores;
+mod hud;
+mod indicators;
+mod locale;
 mod map;
+mod minimap;
 mod movement;
+mod pause;
+mod popups;
 mod powerups;
+mod settings;
+mod sound;
 mod state;
+mod stats;
+mod storage;
 mod survivour;
 mod ui;
 mod waves;
-mod camera;
 mod zombies;
 
+use accessibility::AccessibilityPlugin;
+use achievements::AchievementsPlugin;
 use assets::AssetsPlugin;
+use camera::CameraPlugin;
 use collision::CollisionPlugin;
+use controls::ControlsPlugin;
 use game_conf::GameConfPlugin;
+use game_mode::GameModePlugin;
+use high_scores::HighScoresPlugin;
+use hud::HudPlugin;
+use indicators::IndicatorsPlugin;
+use locale::LocalePlugin;
 use map::MapPlugin;
+use minimap::MinimapPlugin;
+use pause::PausePlugin;
+use popups::PopupsPlugin;
+use settings::SettingsPlugin;
+use sound::SoundPlugin;
 use state::StatePlugin;
+use stats::StatsPlugin;
 use survivour::SurvivourPlugin;
 use ui::UiPlugin;
 use waves::WavesPlugin;
-use camera::CameraPlugin;
 use zombies::ZombiesPlugin;
 
 fn main() {
-    App::new()
-        .add_plugins((
-            GameConfPlugin,
-            StatePlugin,
-            CameraPlugin,
-            AssetsPlugin,
-            UiPlugin,
-            MapPlugin,
-            SurvivourPlugin,
-            ZombiesPlugin,
-            WavesPlugin,
-            CollisionPlugin,
-        ))
-        .run();
+    let mut app = App::new();
+    app.add_plugins((
+        // Settings are loaded first, the window is created with them
+        SettingsPlugin,
+        LocalePlugin,
+        AccessibilityPlugin,
+        GameConfPlugin,
+        GameModePlugin,
+        StatePlugin,
+        CameraPlugin,
+        AssetsPlugin,
+        SoundPlugin,
+        UiPlugin,
+        PausePlugin,
+        ControlsPlugin,
+    ))
+    .add_plugins((
+        MapPlugin,
+        SurvivourPlugin,
+        ZombiesPlugin,
+        WavesPlugin,
+        HudPlugin,
+        PopupsPlugin,
+        IndicatorsPlugin,
+        MinimapPlugin,
+        CollisionPlugin,
+        StatsPlugin,
+        HighScoresPlugin,
+        AchievementsPlugin,
+    ));
+
+    #[cfg(feature = "dev")]
+    app.add_plugins(dev::DevPlugin);
+
+    app.run();
 }
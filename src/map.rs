@@ -15,12 +15,22 @@ impl Plugin for MapPlugin {
     }
 }
 
+pub const TILE_SIZE: f32 = 32.0;
+
+// Half of the map size, in tiles
 #[derive(Component)]
 pub struct MapBounds {
     pub x: f32,
     pub y: f32,
 }
 
+impl MapBounds {
+    // Half of the map size, in world units
+    pub fn half_extents(&self) -> Vec2 {
+        Vec2::new(self.x, self.y) * TILE_SIZE
+    }
+}
+
 fn create_map(mut commands: Commands, graphics: Res<Graphics>) {
     let map_size = TilemapSize { x: 64, y: 64 };
     let tilemap_entity = commands
@@ -44,7 +54,10 @@ fn create_map(mut commands: Commands, graphics: Res<Graphics>) {
         tile_storage.set(&tile_pos, tile_entity);
     });
 
-    let tile_size = TilemapTileSize { x: 32.0, y: 32.0 };
+    let tile_size = TilemapTileSize {
+        x: TILE_SIZE,
+        y: TILE_SIZE,
+    };
     let grid_size = tile_size.into();
     let map_type = TilemapType::default();
 
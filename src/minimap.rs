@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    map::MapBounds,
+    powerups::PowerUp,
+    settings::Settings,
+    state::{gameplay_running, GameState},
+    survivour::{Survivour, SurvivourActions},
+    zombies::Zombie,
+};
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowMinimap>()
+            .insert_resource(MinimapTimer(Timer::from_seconds(
+                MINIMAP_REFRESH,
+                TimerMode::Repeating,
+            )))
+            .add_systems(OnEnter(GameState::Playing), spawn_minimap)
+            .add_systems(
+                Update,
+                (toggle_minimap, update_minimap)
+                    .chain()
+                    .run_if(gameplay_running),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_minimap);
+    }
+}
+
+// Kept between runs, so a hidden minimap stays hidden
+#[derive(Resource)]
+struct ShowMinimap(bool);
+
+impl Default for ShowMinimap {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// The dots are only moved a few times per second
+#[derive(Resource, Deref, DerefMut)]
+struct MinimapTimer(Timer);
+
+// The node covers the whole arena, its border is the edge of the map
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct MinimapDot;
+
+const MINIMAP_SIZE: f32 = 160.0;
+const MINIMAP_REFRESH: f32 = 0.1;
+// Dots are reused, anything past this many isn't drawn
+const MAX_DOTS: usize = 150;
+const SURVIVOUR_DOT: (f32, Color) = (8.0, Color::rgb(0.3, 0.8, 1.0));
+// Zombies and pickups are coloured by the palette picked in the settings
+const ZOMBIE_DOT_SIZE: f32 = 4.0;
+const BLOATER_DOT_SIZE: f32 = 6.0;
+const PICKUP_DOT_SIZE: f32 = 5.0;
+
+fn spawn_minimap(mut cmds: Commands, show: Res<ShowMinimap>) {
+    cmds.spawn((
+        Minimap,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(80.0),
+                right: Val::Px(20.0),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                border: UiRect::all(Val::Px(2.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            border_color: Color::rgba(0.9, 0.9, 0.9, 0.6).into(),
+            visibility: if show.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+    ))
+    .with_children(|minimap| {
+        for _ in 0..MAX_DOTS {
+            minimap.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        display: Display::None,
+                        ..default()
+                    },
+                    ..default()
+                },
+                MinimapDot,
+            ));
+        }
+    });
+}
+
+fn toggle_minimap(
+    survivours: Query<&ActionState<SurvivourActions>, With<Survivour>>,
+    mut show: ResMut<ShowMinimap>,
+    mut minimap: Query<&mut Visibility, With<Minimap>>,
+) {
+    // Either player can toggle it in co-op
+    let toggled = survivours
+        .iter()
+        .any(|actions| actions.just_pressed(SurvivourActions::ToggleMinimap));
+    if !toggled {
+        return;
+    }
+
+    show.0 = !show.0;
+    for mut visibility in minimap.iter_mut() {
+        *visibility = if show.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_minimap(
+    mut timer: ResMut<MinimapTimer>,
+    show: Res<ShowMinimap>,
+    map_bounds: Query<&MapBounds>,
+    survivours: Query<&Transform, With<Survivour>>,
+    zombies: Query<(&Transform, &Zombie)>,
+    powerups: Query<(&Transform, &PowerUp)>,
+    mut dots: Query<(&mut Style, &mut BackgroundColor), With<MinimapDot>>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+) {
+    if !timer.tick(time.delta()).just_finished() || !show.0 {
+        return;
+    }
+    let Ok(map_bounds) = map_bounds.get_single() else {
+        return;
+    };
+    let half_extents = map_bounds.half_extents();
+
+    // Survivours first, so they are always drawn when there are too many zombies
+    let survivour_dots = survivours.iter().map(|tf| (tf, SURVIVOUR_DOT));
+    let palette = settings.accessibility.palette;
+    let powerup_dots = powerups
+        .iter()
+        .map(|(tf, powerup)| (tf, (PICKUP_DOT_SIZE, palette.pickup_color(powerup))));
+    let zombie_dots = zombies.iter().map(|(tf, zombie)| {
+        let size = match zombie {
+            Zombie::Bloater => BLOATER_DOT_SIZE,
+            _ => ZOMBIE_DOT_SIZE,
+        };
+        (tf, (size, palette.zombie_color(zombie)))
+    });
+    let mut markers = survivour_dots.chain(powerup_dots).chain(zombie_dots);
+
+    for (mut style, mut color) in dots.iter_mut() {
+        let Some((tf, (size, dot_color))) = markers.next() else {
+            style.display = Display::None;
+            continue;
+        };
+
+        // From world units to a fraction of the map, with y going down in the UI
+        let position = (tf.translation.truncate() + half_extents) / (half_extents * 2.0);
+        style.display = Display::Flex;
+        style.left = Val::Percent(position.x * 100.0);
+        style.top = Val::Percent((1.0 - position.y) * 100.0);
+        style.width = Val::Px(size);
+        style.height = Val::Px(size);
+        style.margin = UiRect {
+            left: Val::Px(-size / 2.0),
+            top: Val::Px(-size / 2.0),
+            ..default()
+        };
+        *color = dot_color.into();
+    }
+}
+
+fn cleanup_minimap(mut cmds: Commands, minimap: Query<Entity, With<Minimap>>) {
+    for entity in minimap.iter() {
+        cmds.entity(entity).despawn_recursive();
+    }
+}
@@ -1,6 +1,119 @@
 use bevy::prelude::*;
 
-#[derive(Component, Deref, DerefMut)]
+// Shared by the survivour and the zombies, speeds are in world units per second
+#[derive(Component)]
 pub struct MovementSpeed {
-    pub speed: f32,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    // Deceleration when there is no input
+    pub friction: f32,
+}
+
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Velocity(pub Vec2);
+
+impl MovementSpeed {
+    // Accelerate towards `direction`, or slow down to a stop when it's zero.
+    // `speed_scale` multiplies the max speed, e.g. while sprinting.
+    pub fn steer(&self, velocity: &mut Velocity, direction: Vec2, speed_scale: f32, delta: f32) {
+        // Diagonals must not be faster, but a half tilted stick should stay slow
+        let direction = direction.clamp_length_max(1.0);
+
+        if direction == Vec2::ZERO {
+            let slowdown = velocity.0.clamp_length_max(self.friction * delta);
+            velocity.0 -= slowdown;
+        } else {
+            let target = direction * self.max_speed * speed_scale;
+            let change = (target - velocity.0).clamp_length_max(self.acceleration * delta);
+            velocity.0 += change;
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    // Drained per second while sprinting
+    pub drain: f32,
+    // Regenerated per second while not sprinting
+    pub regen: f32,
+    // Set when stamina runs out, sprinting is blocked until it's full again
+    pub exhausted: bool,
+}
+
+impl Stamina {
+    pub fn new(max: f32, drain: f32, regen: f32) -> Self {
+        Self {
+            current: max,
+            max,
+            drain,
+            regen,
+            exhausted: false,
+        }
+    }
+
+    // Returns whether the sprint can go on this frame
+    pub fn update(&mut self, sprinting: bool, delta: f32) -> bool {
+        let can_sprint = sprinting && !self.exhausted;
+
+        if can_sprint {
+            self.current = (self.current - self.drain * delta).max(0.0);
+            self.exhausted = self.current == 0.0;
+        } else {
+            self.current = (self.current + self.regen * delta).min(self.max);
+            if self.current == self.max {
+                self.exhausted = false;
+            }
+        }
+
+        can_sprint
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprinting_drains_stamina() {
+        let mut stamina = Stamina::new(10.0, 4.0, 2.0);
+
+        assert!(stamina.update(true, 1.0));
+        assert_eq!(stamina.current, 6.0);
+        assert!(!stamina.exhausted);
+    }
+
+    #[test]
+    fn running_out_blocks_sprint_until_full() {
+        let mut stamina = Stamina::new(10.0, 4.0, 2.0);
+
+        assert!(stamina.update(true, 3.0));
+        assert_eq!(stamina.current, 0.0);
+        assert!(stamina.exhausted);
+
+        // Still held down, but the survivour is out of breath
+        assert!(!stamina.update(true, 2.0));
+        assert_eq!(stamina.current, 4.0);
+        assert!(stamina.exhausted);
+
+        assert!(!stamina.update(true, 3.0));
+        assert_eq!(stamina.current, 10.0);
+        assert!(!stamina.exhausted);
+        assert!(stamina.update(true, 0.5));
+    }
+
+    #[test]
+    fn regen_stops_at_max() {
+        let mut stamina = Stamina::new(10.0, 4.0, 2.0);
+        stamina.update(true, 1.0);
+
+        assert!(!stamina.update(false, 5.0));
+        assert_eq!(stamina.current, 10.0);
+        assert_eq!(stamina.fraction(), 1.0);
+    }
 }
@@ -0,0 +1,173 @@
+use bevy::{prelude::*, window::WindowFocused};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::{
+    assets::Fonts,
+    locale::LocalizedText,
+    state::{GameState, Overlay, PauseState},
+    ui::{spawn_menu_button, HideUnderOverlay},
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle_pause.run_if(in_state(GameState::Playing).and_then(in_state(Overlay::None))),
+        )
+        .add_systems(OnExit(GameState::Playing), reset_pause)
+        .add_systems(OnEnter(GameState::Restarting), restart);
+
+        app.add_systems(OnEnter(PauseState::Paused), (pause_time, pause_menu))
+            .add_systems(
+                Update,
+                pause_buttons
+                    .run_if(in_state(PauseState::Paused).and_then(in_state(Overlay::None))),
+            )
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (resume_time, cleanup_pause_menu),
+            );
+    }
+}
+
+// All the stuff related to the pause menu
+#[derive(Component)]
+pub struct PauseMenu;
+
+#[derive(Component, EnumIter, Copy, Clone)]
+enum PauseMenuButtons {
+    Resume,
+    Restart,
+    Settings,
+    MainMenu,
+}
+
+impl PauseMenuButtons {
+    // Translation key of the button label
+    pub fn key(&self) -> &'static str {
+        match self {
+            PauseMenuButtons::Resume => "pause.resume",
+            PauseMenuButtons::Restart => "menu.restart",
+            PauseMenuButtons::Settings => "menu.settings",
+            PauseMenuButtons::MainMenu => "pause.quit",
+        }
+    }
+}
+
+// Escape or Start toggle the pause, losing the window focus only pauses
+fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut focus: EventReader<WindowFocused>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    let toggled = keyboard_input.just_pressed(KeyCode::Escape)
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::Start);
+    let focus_lost = focus.read().any(|event| !event.focused);
+
+    match pause_state.get() {
+        PauseState::Running if toggled || focus_lost => {
+            next_pause_state.set(PauseState::Paused);
+        }
+        PauseState::Paused if toggled => {
+            next_pause_state.set(PauseState::Running);
+        }
+        _ => {}
+    }
+}
+
+// Freezes timers and tweens, even in systems that keep running
+fn pause_time(mut time: ResMut<Time<Virtual>>, mut window: Query<&mut Window>) {
+    time.pause();
+    window.single_mut().cursor.visible = true;
+}
+
+fn resume_time(
+    mut time: ResMut<Time<Virtual>>,
+    mut window: Query<&mut Window>,
+    game_state: Res<State<GameState>>,
+) {
+    time.unpause();
+    // The survivours draw their own crosshairs, unless the run is over
+    if *game_state.get() == GameState::Playing {
+        window.single_mut().cursor.visible = false;
+    }
+}
+
+fn reset_pause(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
+
+fn restart(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn pause_menu(mut commands: Commands, fonts: Res<Fonts>) {
+    commands
+        .spawn((
+            PauseMenu,
+            HideUnderOverlay,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 72.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                LocalizedText::new("pause.title"),
+            ));
+
+            for (index, button) in PauseMenuButtons::iter().enumerate() {
+                spawn_menu_button(ui, &fonts, index, button.key(), button);
+            }
+        });
+}
+
+fn pause_buttons(
+    interaction_query: Query<(&Interaction, &PauseMenuButtons), Changed<Interaction>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PauseMenuButtons::Resume => next_pause_state.set(PauseState::Running),
+            PauseMenuButtons::Restart => next_state.set(GameState::Restarting),
+            PauseMenuButtons::Settings => overlay.set(Overlay::Settings),
+            PauseMenuButtons::MainMenu => next_state.set(GameState::MainMenu),
+        }
+    }
+}
+
+fn cleanup_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
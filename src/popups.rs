@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{
+    lens::{TextColorLens, TransformPositionLens},
+    *,
+};
+
+use crate::{
+    assets::Fonts,
+    state::{gameplay_running, GameState},
+};
+
+pub struct PopupsPlugin;
+
+impl Plugin for PopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Popup>()
+            .add_systems(OnEnter(GameState::Playing), spawn_popup_pool)
+            .add_systems(
+                Update,
+                (show_popups, hide_finished_popups).run_if(gameplay_running),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_popups);
+    }
+}
+
+// Text shown floating above the world, for damage, kill score and pickups
+#[derive(Event)]
+pub struct Popup {
+    pub position: Vec2,
+    pub text: String,
+    pub color: Color,
+}
+
+impl Popup {
+    pub fn new(position: Vec2, text: impl Into<String>, color: Color) -> Self {
+        Self {
+            position,
+            text: text.into(),
+            color,
+        }
+    }
+}
+
+pub const DAMAGE_COLOR: Color = Color::rgb(1.0, 0.9, 0.8);
+pub const SCORE_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+pub const PICKUP_COLOR: Color = Color::rgb(0.4, 0.9, 0.4);
+
+// Popups are reused from a fixed pool, the oldest one is recycled when all are in use
+const POOL_SIZE: usize = 64;
+const POPUP_Z: f32 = 50.0;
+const POPUP_RISE: f32 = 40.0;
+const POPUP_DURATION: f32 = 0.8;
+
+// Holds the id of the popup the entity currently shows
+#[derive(Component, Default)]
+struct PopupText(u64);
+
+#[derive(Resource, Default)]
+struct PopupPool {
+    entities: Vec<Entity>,
+    next: usize,
+    shown: u64,
+}
+
+fn spawn_popup_pool(mut cmds: Commands, fonts: Res<Fonts>) {
+    let entities = (0..POOL_SIZE)
+        .map(|_| {
+            cmds.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: fonts.zombiecontrol.clone(),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                PopupText::default(),
+            ))
+            .id()
+        })
+        .collect();
+
+    cmds.insert_resource(PopupPool {
+        entities,
+        ..default()
+    });
+}
+
+fn show_popups(
+    mut cmds: Commands,
+    mut events: EventReader<Popup>,
+    mut pool: ResMut<PopupPool>,
+    mut popups: Query<(&mut PopupText, &mut Text, &mut Transform, &mut Visibility)>,
+) {
+    for event in events.read() {
+        let entity = pool.entities[pool.next];
+        pool.next = (pool.next + 1) % pool.entities.len();
+
+        let Ok((mut popup, mut text, mut transform, mut visibility)) = popups.get_mut(entity)
+        else {
+            continue;
+        };
+        pool.shown += 1;
+        popup.0 = pool.shown;
+        // Spread popups a little, so hits in quick succession don't overlap
+        let start =
+            (event.position + Vec2::new(rand::random::<f32>() * 16.0 - 8.0, 16.0)).extend(POPUP_Z);
+        text.sections[0].value = event.text.clone();
+        text.sections[0].style.color = event.color;
+        transform.translation = start;
+        *visibility = Visibility::Visible;
+
+        let duration = Duration::from_secs_f32(POPUP_DURATION);
+        let rise = Tween::new(
+            EaseFunction::QuadraticOut,
+            duration,
+            TransformPositionLens {
+                start,
+                end: start + Vec3::Y * POPUP_RISE,
+            },
+        )
+        .with_completed_event(popup.0);
+        let fade = Tween::new(
+            EaseFunction::QuadraticIn,
+            duration,
+            TextColorLens {
+                start: event.color,
+                end: event.color.with_a(0.0),
+                section: 0,
+            },
+        );
+        // Replaces the animators of the popup this entity showed before
+        cmds.entity(entity)
+            .insert((Animator::new(rise), Animator::new(fade)));
+    }
+}
+
+fn hide_finished_popups(
+    mut completed: EventReader<TweenCompleted>,
+    mut popups: Query<(&PopupText, &mut Visibility)>,
+) {
+    for event in completed.read() {
+        // A recycled popup may still get the event of the one it showed before
+        if let Ok((popup, mut visibility)) = popups.get_mut(event.entity) {
+            if popup.0 == event.user_data {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+fn cleanup_popups(mut cmds: Commands, popups: Query<Entity, With<PopupText>>) {
+    for entity in popups.iter() {
+        cmds.entity(entity).despawn();
+    }
+    cmds.remove_resource::<PopupPool>();
+}
@@ -0,0 +1,652 @@
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::{
+    accessibility::{ColorPalette, FireMode},
+    assets::{Fonts, Graphics},
+    game_conf::VIRTUAL_RESOLUTION,
+    game_mode::Difficulty,
+    locale::{Language, LocalizedText},
+    state::Overlay,
+    storage,
+    ui::{spawn_menu_button, MenuFocus},
+};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded while building the app, so the window opens with the saved options
+        let settings = storage::try_load::<Settings>(SETTINGS_FILE).unwrap_or_else(|err| {
+            app.insert_resource(SettingsLoadError(err));
+            Settings::default()
+        });
+
+        app.insert_resource(settings)
+            .add_systems(
+                Startup,
+                log_load_error.run_if(resource_exists::<SettingsLoadError>()),
+            )
+            .add_systems(
+                Update,
+                save_settings.run_if(
+                    resource_changed::<Settings>().and_then(not(resource_added::<Settings>())),
+                ),
+            );
+
+        app.add_systems(OnEnter(Overlay::Settings), settings_ui)
+            .add_systems(OnEnter(Overlay::Audio), settings_ui)
+            .add_systems(OnEnter(Overlay::Accessibility), settings_ui)
+            .add_systems(
+                Update,
+                (
+                    settings_buttons,
+                    change_focused_setting,
+                    update_setting_labels,
+                    close_settings,
+                )
+                    .chain()
+                    .run_if(
+                        in_state(Overlay::Settings)
+                            .or_else(in_state(Overlay::Audio))
+                            .or_else(in_state(Overlay::Accessibility)),
+                    ),
+            )
+            .add_systems(OnExit(Overlay::Settings), cleanup_settings)
+            .add_systems(OnExit(Overlay::Audio), cleanup_settings)
+            .add_systems(OnExit(Overlay::Accessibility), cleanup_settings);
+    }
+}
+
+const SETTINGS_FILE: &str = "settings.ron";
+const MIN_TEXT_SCALE: f32 = 0.8;
+const MAX_TEXT_SCALE: f32 = 1.4;
+
+// Logging is set up by `DefaultPlugins`, after the settings were read
+#[derive(Resource)]
+struct SettingsLoadError(String);
+
+fn log_load_error(error: Res<SettingsLoadError>) {
+    warn!("{}", error.0);
+}
+
+// Every tweakable option of the game lives in this resource
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub camera: CameraSettings,
+    pub accessibility: AccessibilitySettings,
+    pub audio: AudioSettings,
+    pub video: VideoSettings,
+    pub gameplay: GameplaySettings,
+    pub language: Language,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    // How fast the camera catches up with its target, higher is snappier
+    pub smoothing: f32,
+    // Size of the rectangle around the camera centre where the target can move freely
+    pub deadzone: Vec2,
+    // Fraction of the survivour to crosshair distance the camera leans towards
+    pub look_ahead: f32,
+    // Upper limit of the look-ahead offset in world units
+    pub max_look_ahead: f32,
+    // Largest distance the camera is pushed away by screen shake, in world units
+    pub shake_offset: f32,
+    // Largest angle the camera is rolled by screen shake, in radians
+    pub shake_rotation: f32,
+    // Amount of trauma removed every second
+    pub shake_decay: f32,
+    // Closest zoom level, smaller values show less of the map
+    pub min_zoom: f32,
+    // Farthest zoom level
+    pub max_zoom: f32,
+    // Zoom out on its own as zombies gather around the survivour
+    pub auto_zoom: bool,
+    // Zombies closer than this to the survivour count for the automatic zoom
+    pub auto_zoom_radius: f32,
+    // Zoom added by each zombie inside the automatic zoom radius
+    pub auto_zoom_per_zombie: f32,
+    // Space kept around the survivours when framing all of them, in world units
+    pub frame_margin: Vec2,
+    // Farthest zoom level allowed to keep every survivour on screen
+    pub frame_max_zoom: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            smoothing: 6.0,
+            deadzone: Vec2::new(120.0, 80.0),
+            look_ahead: 0.25,
+            max_look_ahead: 150.0,
+            shake_offset: 20.0,
+            shake_rotation: 0.05,
+            shake_decay: 1.5,
+            min_zoom: 0.6,
+            max_zoom: 1.6,
+            auto_zoom: false,
+            auto_zoom_radius: 400.0,
+            auto_zoom_per_zombie: 0.04,
+            frame_margin: Vec2::new(300.0, 250.0),
+            frame_max_zoom: 2.5,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // Multiplier for all screen shake, 0 turns it off
+    pub screen_shake: f32,
+    pub hit_stop: bool,
+    // Multiplier for the size of every text
+    pub text_scale: f32,
+    // Solid backgrounds behind the HUD
+    pub high_contrast: bool,
+    pub palette: ColorPalette,
+    pub fire_mode: FireMode,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            screen_shake: 1.0,
+            hit_stop: true,
+            text_scale: 1.0,
+            high_contrast: false,
+            palette: ColorPalette::default(),
+            fire_mode: FireMode::default(),
+        }
+    }
+}
+
+// Volumes go from 0 to 1, every channel is scaled by the master volume
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    // Menu sounds
+    pub ui: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            sfx: 1.0,
+            ui: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum WindowModeSetting {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+
+    // Translation key of the name shown in the settings
+    pub fn key(&self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "window-mode.windowed",
+            WindowModeSetting::Borderless => "window-mode.borderless",
+            WindowModeSetting::Fullscreen => "window-mode.fullscreen",
+        }
+    }
+}
+
+// Window sizes offered in the settings screen
+const RESOLUTIONS: [Vec2; 5] = [
+    VIRTUAL_RESOLUTION,
+    Vec2::new(1280.0, 720.0),
+    Vec2::new(1280.0, 1024.0),
+    Vec2::new(1600.0, 900.0),
+    Vec2::new(1920.0, 1080.0),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub window_mode: WindowModeSetting,
+    pub vsync: bool,
+    // Size of the window when it's not fullscreen
+    pub resolution: Vec2,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::Windowed,
+            vsync: true,
+            resolution: VIRTUAL_RESOLUTION,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, EnumIter)]
+pub enum CrosshairStyle {
+    #[default]
+    Classic,
+    Small,
+    Dot,
+}
+
+impl CrosshairStyle {
+    pub fn size(&self) -> Vec2 {
+        match self {
+            CrosshairStyle::Classic => Vec2::new(48.0, 48.0),
+            CrosshairStyle::Small => Vec2::new(28.0, 28.0),
+            CrosshairStyle::Dot => Vec2::new(8.0, 8.0),
+        }
+    }
+
+    // The dot is a plain square, without the crosshair image
+    pub fn texture(&self, graphics: &Graphics) -> Handle<Image> {
+        match self {
+            CrosshairStyle::Dot => Handle::default(),
+            _ => graphics.crosshair.clone(),
+        }
+    }
+
+    // Translation key of the name shown in the settings
+    pub fn key(&self) -> &'static str {
+        match self {
+            CrosshairStyle::Classic => "crosshair.classic",
+            CrosshairStyle::Small => "crosshair.small",
+            CrosshairStyle::Dot => "crosshair.dot",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameplaySettings {
+    pub crosshair: CrosshairStyle,
+    // Only read when a run starts
+    pub difficulty: Difficulty,
+    // Health bars above damaged zombies
+    pub health_bars: bool,
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            crosshair: CrosshairStyle::default(),
+            difficulty: Difficulty::default(),
+            health_bars: true,
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    storage::save(SETTINGS_FILE, &*settings);
+}
+
+// All the stuff related to the settings screen
+#[derive(Component)]
+pub struct SettingsScreen;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum SettingsRow {
+    Audio,
+    WindowMode,
+    Resolution,
+    Vsync,
+    Crosshair,
+    HealthBars,
+    Difficulty,
+    Language,
+    Accessibility,
+    Controls,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    UiVolume,
+    TextScale,
+    HighContrast,
+    Palette,
+    ScreenShake,
+    HitStop,
+    FireMode,
+    Back,
+}
+
+// Audio and accessibility options get their own pages, they don't fit on the main one
+const SETTINGS_ROWS: [SettingsRow; 11] = [
+    SettingsRow::Audio,
+    SettingsRow::WindowMode,
+    SettingsRow::Resolution,
+    SettingsRow::Vsync,
+    SettingsRow::Crosshair,
+    SettingsRow::HealthBars,
+    SettingsRow::Difficulty,
+    SettingsRow::Language,
+    SettingsRow::Accessibility,
+    SettingsRow::Controls,
+    SettingsRow::Back,
+];
+const AUDIO_ROWS: [SettingsRow; 5] = [
+    SettingsRow::MasterVolume,
+    SettingsRow::MusicVolume,
+    SettingsRow::SfxVolume,
+    SettingsRow::UiVolume,
+    SettingsRow::Back,
+];
+const ACCESSIBILITY_ROWS: [SettingsRow; 7] = [
+    SettingsRow::TextScale,
+    SettingsRow::HighContrast,
+    SettingsRow::Palette,
+    SettingsRow::ScreenShake,
+    SettingsRow::HitStop,
+    SettingsRow::FireMode,
+    SettingsRow::Back,
+];
+
+// Rows of the page shown for an overlay, in the order of the buttons
+fn page_rows(overlay: &Overlay) -> &'static [SettingsRow] {
+    match overlay {
+        Overlay::Audio => &AUDIO_ROWS,
+        Overlay::Accessibility => &ACCESSIBILITY_ROWS,
+        _ => &SETTINGS_ROWS,
+    }
+}
+
+// Where the back button of a page leads
+fn previous_overlay(overlay: &Overlay) -> Overlay {
+    match overlay {
+        Overlay::Audio | Overlay::Accessibility => Overlay::Settings,
+        _ => Overlay::None,
+    }
+}
+
+impl SettingsRow {
+    fn label(&self, settings: &Settings) -> LocalizedText {
+        let percent = |key: &'static str, value: f32| {
+            LocalizedText::new(key).with_arg("value", format!("{:.0}%", value * 100.0))
+        };
+        let on_off = |key: &'static str, value: bool| {
+            LocalizedText::new(key)
+                .with_key_arg("value", if value { "settings.on" } else { "settings.off" })
+        };
+
+        match self {
+            SettingsRow::MasterVolume => percent("settings.master-volume", settings.audio.master),
+            SettingsRow::MusicVolume => percent("settings.music-volume", settings.audio.music),
+            SettingsRow::SfxVolume => percent("settings.sfx-volume", settings.audio.sfx),
+            SettingsRow::UiVolume => percent("settings.ui-volume", settings.audio.ui),
+            SettingsRow::WindowMode => LocalizedText::new("settings.window")
+                .with_key_arg("value", settings.video.window_mode.key()),
+            SettingsRow::Resolution => LocalizedText::new("settings.resolution").with_arg(
+                "value",
+                format!(
+                    "{}x{}",
+                    settings.video.resolution.x, settings.video.resolution.y
+                ),
+            ),
+            SettingsRow::Vsync => on_off("settings.vsync", settings.video.vsync),
+            SettingsRow::ScreenShake => {
+                percent("settings.screen-shake", settings.accessibility.screen_shake)
+            }
+            SettingsRow::Crosshair => LocalizedText::new("settings.crosshair")
+                .with_key_arg("value", settings.gameplay.crosshair.key()),
+            SettingsRow::HealthBars => {
+                on_off("settings.health-bars", settings.gameplay.health_bars)
+            }
+            SettingsRow::Difficulty => LocalizedText::new("settings.difficulty")
+                .with_key_arg("value", settings.gameplay.difficulty.key()),
+            // Language names are never translated, so players can find their own
+            SettingsRow::Language => {
+                LocalizedText::new("settings.language").with_arg("value", settings.language.name())
+            }
+            SettingsRow::Audio => "settings.audio".into(),
+            SettingsRow::Accessibility => "settings.accessibility".into(),
+            SettingsRow::Controls => "settings.controls".into(),
+            SettingsRow::TextScale => {
+                percent("settings.text-scale", settings.accessibility.text_scale)
+            }
+            SettingsRow::HighContrast => on_off(
+                "settings.high-contrast",
+                settings.accessibility.high_contrast,
+            ),
+            SettingsRow::Palette => LocalizedText::new("settings.palette")
+                .with_key_arg("value", settings.accessibility.palette.key()),
+            SettingsRow::HitStop => on_off("settings.hit-stop", settings.accessibility.hit_stop),
+            SettingsRow::FireMode => LocalizedText::new("settings.fire-mode")
+                .with_key_arg("value", settings.accessibility.fire_mode.key()),
+            SettingsRow::Back => "menu.back".into(),
+        }
+    }
+
+    // Moves the option one step forward or backward, wrapping around lists
+    fn change(&self, settings: &mut Settings, step: i32) {
+        let volume = |value: &mut f32| *value = (*value + step as f32 * 0.1).clamp(0.0, 1.0);
+
+        match self {
+            SettingsRow::MasterVolume => volume(&mut settings.audio.master),
+            SettingsRow::MusicVolume => volume(&mut settings.audio.music),
+            SettingsRow::SfxVolume => volume(&mut settings.audio.sfx),
+            SettingsRow::UiVolume => volume(&mut settings.audio.ui),
+            SettingsRow::WindowMode => {
+                settings.video.window_mode = cycle(settings.video.window_mode, step);
+            }
+            SettingsRow::Resolution => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|resolution| *resolution == settings.video.resolution)
+                    .unwrap_or(0);
+                let next = (current as i32 + step).rem_euclid(RESOLUTIONS.len() as i32);
+                settings.video.resolution = RESOLUTIONS[next as usize];
+            }
+            SettingsRow::Vsync => settings.video.vsync = !settings.video.vsync,
+            SettingsRow::ScreenShake => {
+                let shake = &mut settings.accessibility.screen_shake;
+                *shake = (*shake + step as f32 * 0.25).clamp(0.0, 1.0);
+            }
+            SettingsRow::Crosshair => {
+                settings.gameplay.crosshair = cycle(settings.gameplay.crosshair, step);
+            }
+            SettingsRow::HealthBars => {
+                settings.gameplay.health_bars = !settings.gameplay.health_bars;
+            }
+            SettingsRow::Difficulty => {
+                settings.gameplay.difficulty = cycle(settings.gameplay.difficulty, step);
+            }
+            SettingsRow::Language => settings.language = cycle(settings.language, step),
+            SettingsRow::TextScale => {
+                let scale = &mut settings.accessibility.text_scale;
+                *scale = (*scale + step as f32 * 0.1).clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+            }
+            SettingsRow::HighContrast => {
+                settings.accessibility.high_contrast = !settings.accessibility.high_contrast;
+            }
+            SettingsRow::Palette => {
+                settings.accessibility.palette = cycle(settings.accessibility.palette, step);
+            }
+            SettingsRow::HitStop => {
+                settings.accessibility.hit_stop = !settings.accessibility.hit_stop;
+            }
+            SettingsRow::FireMode => {
+                settings.accessibility.fire_mode = cycle(settings.accessibility.fire_mode, step);
+            }
+            SettingsRow::Audio
+            | SettingsRow::Accessibility
+            | SettingsRow::Controls
+            | SettingsRow::Back => {}
+        }
+    }
+}
+
+fn cycle<T: IntoEnumIterator + PartialEq + Copy>(value: T, step: i32) -> T {
+    let values: Vec<T> = T::iter().collect();
+    let current = values.iter().position(|v| *v == value).unwrap_or(0);
+    let next = (current as i32 + step).rem_euclid(values.len() as i32);
+    values[next as usize]
+}
+
+fn settings_ui(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    settings: Res<Settings>,
+    overlay: Res<State<Overlay>>,
+) {
+    let title = match overlay.get() {
+        Overlay::Audio => "audio.title",
+        Overlay::Accessibility => "accessibility.title",
+        _ => "settings.title",
+    };
+
+    commands
+        .spawn((
+            SettingsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                // Draw on top of the screen that opened it
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 56.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                LocalizedText::new(title),
+            ));
+
+            for (index, row) in page_rows(overlay.get()).iter().enumerate() {
+                spawn_menu_button(ui, &fonts, index, row.label(&settings), *row);
+            }
+
+            ui.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 24.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                LocalizedText::new("settings.hint"),
+            ));
+        });
+}
+
+fn settings_buttons(
+    interaction_query: Query<(&Interaction, &SettingsRow), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    current_overlay: Res<State<Overlay>>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    for (interaction, row) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match row {
+            SettingsRow::Audio => overlay.set(Overlay::Audio),
+            SettingsRow::Accessibility => overlay.set(Overlay::Accessibility),
+            SettingsRow::Controls => overlay.set(Overlay::Controls),
+            SettingsRow::Back => overlay.set(previous_overlay(current_overlay.get())),
+            _ => row.change(&mut settings, 1),
+        }
+    }
+}
+
+fn change_focused_setting(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    focus: Res<MenuFocus>,
+    mut settings: ResMut<Settings>,
+    overlay: Res<State<Overlay>>,
+) {
+    let gamepad_pressed = |button_type: GamepadButtonType| {
+        gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == button_type)
+    };
+
+    let step = if keyboard_input.just_pressed(KeyCode::Left)
+        || gamepad_pressed(GamepadButtonType::DPadLeft)
+    {
+        -1
+    } else if keyboard_input.just_pressed(KeyCode::Right)
+        || gamepad_pressed(GamepadButtonType::DPadRight)
+    {
+        1
+    } else {
+        return;
+    };
+
+    // Buttons are spawned in the order of the rows
+    if let Some(row) = page_rows(overlay.get()).get(focus.0) {
+        row.change(&mut settings, step);
+    }
+}
+
+fn update_setting_labels(
+    settings: Res<Settings>,
+    rows: Query<(&SettingsRow, &Children)>,
+    mut texts: Query<&mut LocalizedText>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (row, children) in rows.iter() {
+        if let Ok(mut text) = texts.get_mut(children[0]) {
+            *text = row.label(&settings);
+        }
+    }
+}
+
+fn close_settings(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    current_overlay: Res<State<Overlay>>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    let gamepad_back = gamepad_buttons
+        .get_just_pressed()
+        .any(|button| button.button_type == GamepadButtonType::East);
+
+    if gamepad_back || keyboard_input.just_pressed(KeyCode::Escape) {
+        overlay.set(previous_overlay(current_overlay.get()));
+    }
+}
+
+fn cleanup_settings(mut commands: Commands, query: Query<Entity, With<SettingsScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
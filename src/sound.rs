@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::{AudioSource, *};
+
+use crate::{assets::Sounds, settings::Settings, state::PauseState};
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(AudioPlugin)
+            .add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<SfxChannel>()
+            .add_audio_channel::<UiChannel>()
+            .add_event::<PlaySfx>()
+            .add_systems(
+                Update,
+                (
+                    apply_volume.run_if(
+                        resource_changed::<Settings>().or_else(state_changed::<PauseState>()),
+                    ),
+                    pause_sfx.run_if(state_changed::<PauseState>()),
+                    // The sounds are only there once the loading is done
+                    play_sfx.run_if(resource_exists::<Sounds>()),
+                ),
+            );
+    }
+}
+
+// Each channel has its own volume in the settings, scaled by the master volume
+#[derive(Resource)]
+pub struct MusicChannel;
+
+#[derive(Resource)]
+pub struct SfxChannel;
+
+#[derive(Resource)]
+pub struct UiChannel;
+
+// Music is turned down while the pause menu is open
+const PAUSE_MUSIC_VOLUME: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sfx {
+    Shoot,
+    Hit,
+    Splat,
+    Pickup,
+    // Played on the UI channel, when a menu button is pressed
+    MenuSelect,
+}
+
+impl Sfx {
+    fn source(&self, sounds: &Sounds) -> Handle<AudioSource> {
+        match self {
+            Sfx::Shoot => sounds.shoot.clone(),
+            Sfx::Hit => sounds.hit.clone(),
+            Sfx::Splat => sounds.splat.clone(),
+            Sfx::Pickup => sounds.pickup.clone(),
+            Sfx::MenuSelect => sounds.powerup.clone(),
+        }
+    }
+}
+
+// Sent instead of playing sounds directly, so every sound goes through its channel
+#[derive(Event)]
+pub struct PlaySfx(pub Sfx);
+
+fn apply_volume(
+    settings: Res<Settings>,
+    pause_state: Res<State<PauseState>>,
+    music: Res<AudioChannel<MusicChannel>>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    ui: Res<AudioChannel<UiChannel>>,
+) {
+    let audio = &settings.audio;
+    let ducking = if *pause_state.get() == PauseState::Paused {
+        PAUSE_MUSIC_VOLUME
+    } else {
+        1.0
+    };
+
+    music.set_volume((audio.master * audio.music * ducking) as f64);
+    sfx.set_volume((audio.master * audio.sfx) as f64);
+    ui.set_volume((audio.master * audio.ui) as f64);
+}
+
+// Gameplay sounds still playing are held while the game is paused
+fn pause_sfx(pause_state: Res<State<PauseState>>, sfx: Res<AudioChannel<SfxChannel>>) {
+    match pause_state.get() {
+        PauseState::Paused => {
+            sfx.pause();
+        }
+        PauseState::Running => {
+            sfx.resume();
+        }
+    }
+}
+
+fn play_sfx(
+    mut events: EventReader<PlaySfx>,
+    sounds: Res<Sounds>,
+    sfx: Res<AudioChannel<SfxChannel>>,
+    ui: Res<AudioChannel<UiChannel>>,
+) {
+    for PlaySfx(sound) in events.read() {
+        let source = sound.source(&sounds);
+        match sound {
+            Sfx::MenuSelect => ui.play(source),
+            _ => sfx.play(source),
+        };
+    }
+}
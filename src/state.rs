@@ -4,7 +4,9 @@ pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>();
+        app.add_state::<GameState>()
+            .add_state::<PauseState>()
+            .add_state::<Overlay>();
     }
 }
 
@@ -15,4 +17,41 @@ pub enum GameState {
     MainMenu,
     Playing,
     GameOver,
+    // Passed through on restart, so the `Playing` cleanup and setup run again
+    Restarting,
+    // Some game files are missing or broken, the game stops on an error screen
+    AssetError,
+}
+
+// Only meaningful while `GameState::Playing`
+#[derive(Default, Debug, Clone, Eq, PartialEq, States, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// Screens drawn on top of the current game state
+#[derive(Default, Debug, Clone, Eq, PartialEq, States, Hash)]
+pub enum Overlay {
+    #[default]
+    None,
+    Settings,
+    // Pages of the settings
+    Audio,
+    Accessibility,
+    Controls,
+    HighScores,
+    Credits,
+    Achievements,
+    // Typing a name for a new high score
+    NameEntry,
+}
+
+// Run condition for gameplay systems, which freeze while the game is paused
+pub fn gameplay_running(
+    game_state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+) -> bool {
+    *game_state.get() == GameState::Playing && *pause_state.get() == PauseState::Running
 }
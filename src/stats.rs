@@ -0,0 +1,52 @@
+use bevy::{prelude::*, utils::Duration};
+
+use crate::{
+    state::{gameplay_running, GameState},
+    waves::ZombieCount,
+    zombies::Zombie,
+};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_systems(OnEnter(GameState::Playing), reset_stats)
+            .add_systems(Update, track_time.run_if(gameplay_running));
+    }
+}
+
+// What happened during the current run, shown on the game over screen.
+// The score and the wave live in their own resources
+#[derive(Resource, Default, Clone)]
+pub struct RunStats {
+    pub time_survived: Duration,
+    pub kills: ZombieCount,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub damage_taken: i32,
+    pub pickups_collected: u32,
+}
+
+impl RunStats {
+    pub fn add_kill(&mut self, zombie: &Zombie) {
+        self.kills.increase_count(zombie);
+    }
+
+    // Fraction of the shots that hit a zombie, `None` before the first shot
+    pub fn accuracy(&self) -> Option<f32> {
+        if self.shots_fired == 0 {
+            return None;
+        }
+        Some(self.shots_hit as f32 / self.shots_fired as f32)
+    }
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+// Paused time doesn't count, the virtual clock is stopped
+fn track_time(mut stats: ResMut<RunStats>, time: Res<Time>) {
+    stats.time_survived += time.delta();
+}
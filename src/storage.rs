@@ -0,0 +1,114 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+// Everything the game saves goes in the user's config directory
+fn file_path(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("zombie_arena").join(file_name))
+}
+
+// Read a saved file, falling back to the default value if it's missing or broken
+pub fn load<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    try_load(file_name).unwrap_or_else(|err| {
+        warn!("{}", err);
+        T::default()
+    })
+}
+
+// Same as `load`, but a broken file is returned as an error for the caller to report
+pub fn try_load<T: DeserializeOwned + Default>(file_name: &str) -> Result<T, String> {
+    match file_path(file_name) {
+        Some(path) => read(&path),
+        None => Ok(T::default()),
+    }
+}
+
+// A missing file is not an error, nothing was saved yet
+fn read<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Ok(T::default());
+    };
+
+    ron::from_str(&contents)
+        .map_err(|err| format!("Failed to read {}, using defaults: {}", path.display(), err))
+}
+
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let Some(path) = file_path(file_name) else {
+        warn!("No config directory, {} was not saved", file_name);
+        return;
+    };
+
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, contents).map_err(|err| err.to_string())
+        });
+
+    if let Err(err) = result {
+        error!("Failed to save {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Default, PartialEq, Debug)]
+    #[serde(default)]
+    struct Saved {
+        volume: f32,
+        name: String,
+    }
+
+    fn temp_file(name: &str, contents: Option<&str>) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("zombie_arena_test_{}_{}", std::process::id(), name));
+        match contents {
+            Some(contents) => fs::write(&path, contents).unwrap(),
+            None => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+        path
+    }
+
+    #[test]
+    fn missing_file_uses_defaults() {
+        let path = temp_file("missing.ron", None);
+
+        assert_eq!(read::<Saved>(&path), Ok(Saved::default()));
+    }
+
+    #[test]
+    fn broken_file_is_an_error() {
+        let path = temp_file("broken.ron", Some("(volume: "));
+
+        assert!(read::<Saved>(&path).is_err());
+        assert_eq!(read::<Saved>(&path).unwrap_or_default(), Saved::default());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let path = temp_file("partial.ron", Some("(volume: 0.5)"));
+
+        assert_eq!(
+            read::<Saved>(&path),
+            Ok(Saved {
+                volume: 0.5,
+                name: String::new(),
+            })
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
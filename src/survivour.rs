@@ -1,14 +1,23 @@
-use crate::assets::{Fonts, Sounds};
+use crate::accessibility::FireMode;
 use crate::collision::CollisionSize;
-use crate::combat::{AttackDelay, CombatBundle, Health};
+use crate::combat::{AttackDelay, CombatBundle, Health, MaxHealth};
+use crate::controls::{Binding, Controls};
+use crate::game_mode::GameMode;
 use crate::map::MapBounds;
-use crate::movement::MovementSpeed;
-use crate::{assets::Graphics, camera::GameCamera, state::GameState};
+use crate::movement::{MovementSpeed, Stamina, Velocity};
+use crate::settings::{CrosshairStyle, Settings};
+use crate::sound::{PlaySfx, Sfx};
+use crate::stats::RunStats;
+use crate::{
+    assets::Graphics,
+    state::{gameplay_running, GameState},
+};
 use bevy::prelude::*;
 use bevy::utils::Duration;
 use bevy::window::PrimaryWindow;
-use bevy_kira_audio::prelude::*;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
 
 pub struct SurvivourPlugin;
 
@@ -18,28 +27,37 @@ impl Plugin for SurvivourPlugin {
 
         app.init_resource::<MouseWorldCoords>();
 
-        app.add_systems(OnEnter(GameState::Playing), (spawn_cursor, spawn_survivour))
+        app.add_systems(OnEnter(GameState::Playing), (hide_cursor, spawn_survivours))
             .add_systems(
                 Update,
-                (survivour_walks, update_camera)
+                (start_dodge, survivour_walks)
                     .chain()
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(gameplay_running),
             )
             .add_systems(
                 Update,
                 (
+                    detect_aim_device,
                     update_mouse_world_coords,
+                    update_crosshairs,
                     update_cursor,
                     look_at_cursor,
                     shoot_bullet,
                     update_bullet,
                 )
                     .chain()
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(gameplay_running),
             )
             .add_systems(
                 Update,
-                update_heart_health.run_if(in_state(GameState::Playing)),
+                apply_crosshair_style
+                    .run_if(in_state(GameState::Playing).and_then(resource_changed::<Settings>())),
+            )
+            .add_systems(
+                Update,
+                (dim_downed, revive_downed, check_all_down)
+                    .chain()
+                    .run_if(gameplay_running),
             );
 
         app.add_systems(OnExit(GameState::Playing), cleanup);
@@ -48,81 +66,222 @@ impl Plugin for SurvivourPlugin {
 
 const SURVIVOUR_Z: f32 = 2.0;
 const BULLET_Z: f32 = 2.5;
+// Distance between the survivour and the crosshair when aiming with a gamepad
+const GAMEPAD_AIM_RADIUS: f32 = 200.0;
+const SPRINT_SCALE: f32 = 1.6;
+const DODGE_DISTANCE: f32 = 160.0;
+const DODGE_DURATION: f32 = 0.25;
+const DODGE_COOLDOWN: f32 = 1.2;
+// A downed survivour is revived by standing this close for `REVIVE_TIME` seconds
+pub const REVIVE_RADIUS: f32 = 70.0;
+const REVIVE_TIME: f32 = 3.0;
+const REVIVE_HEALTH: i32 = 2;
+const MAX_HEALTH: i32 = 5;
+// Tints telling the survivours apart in co-op
+const PLAYER_COLORS: [Color; 2] = [Color::WHITE, Color::rgb(0.6, 0.8, 1.0)];
+const DOWNED_COLOR: Color = Color::rgb(0.35, 0.35, 0.35);
 
 #[derive(Component)]
 pub struct Survivour;
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
-enum SurvivourActions {
+// Index of the player controlling a survivour, 0 is player one
+#[derive(Component, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct Player(pub usize);
+
+// Out of health, waiting for the other player to revive them
+#[derive(Component)]
+pub struct Downed {
+    pub revive: Timer,
+}
+
+impl Default for Downed {
+    fn default() -> Self {
+        Self {
+            revive: Timer::from_seconds(REVIVE_TIME, TimerMode::Once),
+        }
+    }
+}
+
+// Which devices a survivour listens to
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum InputDevices {
+    // Solo play, every device moves the only survivour
+    All,
+    KeyboardMouse,
+    Gamepad,
+}
+
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    Reflect,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+pub enum SurvivourActions {
     Up,
     Down,
     Left,
     Right,
+    // Analog movement, on top of the digital directions
+    Move,
+    Aim,
     Shoot,
+    Sprint,
+    Dodge,
+    ToggleMinimap,
 }
 
 #[derive(Bundle)]
-struct SurvivourBundle {
+pub struct SurvivourBundle {
     survivour: Survivour,
     input_manager: InputManagerBundle<SurvivourActions>,
 }
 
+// The survivour is rolling and can't be hurt
+#[derive(Component)]
+pub struct Dodging {
+    pub timer: Timer,
+    pub direction: Vec2,
+}
+
+#[derive(Component, Deref, DerefMut)]
+pub struct DodgeCooldown(pub Timer);
+
+impl Default for DodgeCooldown {
+    fn default() -> Self {
+        // Start ready to dodge
+        let mut timer = Timer::from_seconds(DODGE_COOLDOWN, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+// Whether the survivour keeps shooting, when the fire mode is a toggle
+#[derive(Component, Default)]
+struct FireToggle(bool);
+
 #[derive(Component)]
 pub struct Bullet {
     pub speed: f32,
     pub start_position: Vec2,
 }
 
+impl SurvivourActions {
+    // Translation key of the name shown in the controls screen
+    pub fn key(&self) -> &'static str {
+        match self {
+            SurvivourActions::Up => "action.up",
+            SurvivourActions::Down => "action.down",
+            SurvivourActions::Left => "action.left",
+            SurvivourActions::Right => "action.right",
+            SurvivourActions::Move => "action.move",
+            SurvivourActions::Aim => "action.aim",
+            SurvivourActions::Shoot => "action.shoot",
+            SurvivourActions::Sprint => "action.sprint",
+            SurvivourActions::Dodge => "action.dodge",
+            SurvivourActions::ToggleMinimap => "action.toggle-minimap",
+        }
+    }
+}
+
 impl SurvivourBundle {
-    fn default_input_map() -> InputMap<SurvivourActions> {
-        // This allows us to replace `ArpgAction::Up` with `Up`,
-        // significantly reducing boilerplate
-        use SurvivourActions::*;
+    pub fn input_map(controls: &Controls, devices: InputDevices) -> InputMap<SurvivourActions> {
         let mut input_map = InputMap::<SurvivourActions>::default();
+        let keyboard = devices != InputDevices::Gamepad;
+        let gamepad = devices != InputDevices::KeyboardMouse;
+
+        // Buttons can be rebound by the player
+        if keyboard {
+            for (action, binding) in controls.keyboard.iter() {
+                match *binding {
+                    Binding::Key(key) => input_map.insert(key, *action),
+                    Binding::Mouse(button) => input_map.insert(button, *action),
+                    Binding::Gamepad(button) => input_map.insert(button, *action),
+                };
+            }
+        }
 
-        // Movement
-        input_map.insert(KeyCode::W, Up);
-        input_map.insert(GamepadButtonType::DPadUp, Up);
-
-        input_map.insert(KeyCode::S, Down);
-        input_map.insert(GamepadButtonType::DPadDown, Down);
-
-        input_map.insert(KeyCode::A, Left);
-        input_map.insert(GamepadButtonType::DPadLeft, Left);
-
-        input_map.insert(KeyCode::D, Right);
-        input_map.insert(GamepadButtonType::DPadRight, Right);
+        if gamepad {
+            for (action, binding) in controls.gamepad.iter() {
+                if let Binding::Gamepad(button) = *binding {
+                    input_map.insert(button, *action);
+                }
+            }
 
-        input_map.insert(MouseButton::Left, Shoot);
+            // The sticks are always the same
+            input_map.insert(DualAxis::left_stick(), SurvivourActions::Move);
+            input_map.insert(DualAxis::right_stick(), SurvivourActions::Aim);
+        }
 
         input_map
     }
 }
 
 #[derive(Resource, Default, Deref, DerefMut)]
-struct MouseWorldCoords(Vec2);
+pub struct MouseWorldCoords(pub Vec2);
+
+// World position a survivour aims at, whatever device is moving it
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Crosshair(pub Vec2);
+
+// The device the player aimed with last
+#[derive(Component, PartialEq, Eq)]
+pub enum AimDevice {
+    Mouse,
+    Gamepad,
+}
 
+// Crosshair sprite of the survivour `owner`
 #[derive(Component)]
-pub struct GameCursor;
+pub struct GameCursor {
+    pub owner: Entity,
+}
 
-fn spawn_cursor(mut commands: Commands, graphics: Res<Graphics>, mut window: Query<&mut Window>) {
+// Every survivour has their own crosshair sprite instead
+fn hide_cursor(mut window: Query<&mut Window>) {
     let mut window = window.single_mut();
     window.cursor.visible = false;
+}
 
-    commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(48.0, 48.0)),
-                ..default()
-            },
-            texture: graphics.crosshair.clone(),
-            transform: Transform::from_xyz(0., 0., 100.),
-            ..default()
-        },
-        GameCursor,
-    ));
+fn detect_aim_device(
+    mut cursor_moved: EventReader<CursorMoved>,
+    mut survivours: Query<
+        (
+            &ActionState<SurvivourActions>,
+            &InputDevices,
+            &mut AimDevice,
+        ),
+        With<Survivour>,
+    >,
+) {
+    let mouse_moved = cursor_moved.read().count() > 0;
 
-    println!("Cursor spawned");
+    for (actions, devices, mut aim_device) in survivours.iter_mut() {
+        // In co-op every player keeps their own device
+        if *devices != InputDevices::All {
+            continue;
+        }
+
+        let stick_moved = actions
+            .axis_pair(SurvivourActions::Aim)
+            .is_some_and(|axis| axis.xy() != Vec2::ZERO);
+
+        // Only write on an actual switch, to keep change detection meaningful
+        if stick_moved && *aim_device != AimDevice::Gamepad {
+            *aim_device = AimDevice::Gamepad;
+        } else if mouse_moved && !stick_moved && *aim_device != AimDevice::Mouse {
+            *aim_device = AimDevice::Mouse;
+        }
+    }
 }
 
 fn update_mouse_world_coords(
@@ -144,220 +303,388 @@ fn update_mouse_world_coords(
     }
 }
 
-fn update_cursor(
-    mut game_cursor: Query<&mut Transform, With<GameCursor>>,
+fn update_crosshairs(
+    mut survivours: Query<
+        (
+            &Transform,
+            &ActionState<SurvivourActions>,
+            &AimDevice,
+            &mut Crosshair,
+        ),
+        With<Survivour>,
+    >,
     coords: Res<MouseWorldCoords>,
 ) {
-    let Ok(mut tf) = game_cursor.get_single_mut() else {
-        return;
-    };
+    for (survivour_tf, actions, aim_device, mut crosshair) in survivours.iter_mut() {
+        if *aim_device == AimDevice::Mouse {
+            crosshair.0 = coords.0;
+            continue;
+        }
+
+        // Keep the crosshair where it was when the stick is released
+        let direction = actions
+            .axis_pair(SurvivourActions::Aim)
+            .map(|axis| axis.xy())
+            .filter(|direction| *direction != Vec2::ZERO)
+            .unwrap_or_else(|| survivour_tf.rotation.mul_vec3(Vec3::X).truncate());
 
-    tf.translation.x = coords.x;
-    tf.translation.y = coords.y;
+        crosshair.0 = survivour_tf.translation.truncate()
+            + direction.normalize_or_zero() * GAMEPAD_AIM_RADIUS;
+    }
 }
 
-#[derive(Component)]
-pub struct HealthHeart;
+fn update_cursor(
+    mut game_cursor: Query<(&GameCursor, &mut Transform)>,
+    crosshairs: Query<&Crosshair>,
+) {
+    for (cursor, mut tf) in game_cursor.iter_mut() {
+        let Ok(crosshair) = crosshairs.get(cursor.owner) else {
+            continue;
+        };
 
-#[derive(Component)]
-pub struct HealthText;
+        tf.translation.x = crosshair.x;
+        tf.translation.y = crosshair.y;
+    }
+}
 
-fn spawn_survivour(mut cmds: Commands, graphics: Res<Graphics>, font: Res<Fonts>) {
-    cmds.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(64.0, 64.0)),
+// The crosshair can be changed from the pause menu settings
+fn apply_crosshair_style(
+    settings: Res<Settings>,
+    graphics: Res<Graphics>,
+    mut game_cursor: Query<(&mut Sprite, &mut Handle<Image>), With<GameCursor>>,
+) {
+    let crosshair = settings.gameplay.crosshair;
+
+    for (mut sprite, mut texture) in game_cursor.iter_mut() {
+        sprite.custom_size = Some(crosshair.size());
+        *texture = crosshair.texture(&graphics);
+    }
+}
+
+fn spawn_survivours(
+    mut cmds: Commands,
+    graphics: Res<Graphics>,
+    controls: Res<Controls>,
+    game_mode: Res<GameMode>,
+    settings: Res<Settings>,
+) {
+    let devices = match *game_mode {
+        GameMode::Solo => vec![InputDevices::All],
+        GameMode::Coop => vec![InputDevices::KeyboardMouse, InputDevices::Gamepad],
+    };
+    let count = devices.len();
+
+    for (index, devices) in devices.into_iter().enumerate() {
+        // Side by side around the middle of the map
+        let x = (index as f32 - (count - 1) as f32 / 2.0) * 80.0;
+        spawn_survivour(
+            &mut cmds,
+            &graphics,
+            &controls,
+            settings.gameplay.crosshair,
+            Player(index),
+            devices,
+            Vec2::new(x, 0.0),
+        );
+    }
+}
+
+fn spawn_survivour(
+    cmds: &mut Commands,
+    graphics: &Graphics,
+    controls: &Controls,
+    crosshair: CrosshairStyle,
+    player: Player,
+    devices: InputDevices,
+    position: Vec2,
+) {
+    let color = PLAYER_COLORS[player.0];
+    let aim_device = if devices == InputDevices::Gamepad {
+        AimDevice::Gamepad
+    } else {
+        AimDevice::Mouse
+    };
+
+    let survivour = cmds
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(64.0, 64.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(SURVIVOUR_Z)),
+                texture: graphics.player.clone(),
                 ..default()
             },
-            transform: Transform::from_xyz(0.0, 0., SURVIVOUR_Z),
-            texture: graphics.player.clone(),
-            ..default()
-        },
-        InputManagerBundle {
-            input_map: SurvivourBundle::default_input_map(),
-            ..default()
-        },
-        Survivour,
-        CombatBundle {
-            health: Health(5),
-            attack_delay: AttackDelay {
-                delay: Timer::new(Duration::from_secs_f32(0.6), TimerMode::Once),
+            InputManagerBundle {
+                input_map: SurvivourBundle::input_map(controls, devices),
+                ..default()
             },
-        },
-        MovementSpeed { speed: 10.0 },
-        CollisionSize(Vec2::new(32.0, 32.0)),
-    ));
+            Survivour,
+            player,
+            devices,
+            aim_device,
+            Crosshair(position),
+            CombatBundle {
+                health: Health(MAX_HEALTH),
+                attack_delay: AttackDelay {
+                    delay: Timer::new(Duration::from_secs_f32(0.6), TimerMode::Once),
+                },
+            },
+            MovementSpeed {
+                max_speed: 150.0,
+                acceleration: 1200.0,
+                friction: 1500.0,
+            },
+            MaxHealth(MAX_HEALTH),
+            FireToggle::default(),
+            Velocity::default(),
+            Stamina::new(100.0, 35.0, 20.0),
+            DodgeCooldown::default(),
+            CollisionSize(Vec2::new(32.0, 32.0)),
+        ))
+        .id();
 
     cmds.spawn((
-        HealthHeart,
         SpriteBundle {
             sprite: Sprite {
-                custom_size: Some(Vec2::new(24.0 + 10.0, 24.0 + 10.0)),
+                color,
+                custom_size: Some(crosshair.size()),
                 ..default()
             },
-            transform: Transform::from_xyz(0.0, 0.0, SURVIVOUR_Z),
-            texture: graphics.heart.clone(),
+            texture: crosshair.texture(graphics),
+            transform: Transform::from_xyz(0., 0., 100.),
             ..default()
         },
-    ))
-    .with_children(|parent| {
-        parent.spawn((
-            Text2dBundle {
-                text: Text {
-                    sections: vec![TextSection {
-                        value: "5".to_string(),
-                        style: TextStyle {
-                            font: font.zombiecontrol.clone(),
-                            font_size: 16.0,
-                            color: Color::WHITE,
-                        },
-                    }],
-                    alignment: TextAlignment::Right,
-                    ..default()
-                },
-                transform: Transform::from_xyz(0.0, 0.0, 0.1),
-                ..default()
-            },
-            HealthText,
-        ));
-    });
+        GameCursor { owner: survivour },
+    ));
+}
+
+fn start_dodge(
+    mut cmds: Commands,
+    mut survivours: Query<
+        (
+            Entity,
+            &ActionState<SurvivourActions>,
+            &Transform,
+            &Velocity,
+            &mut DodgeCooldown,
+            &mut Sprite,
+        ),
+        (With<Survivour>, Without<Dodging>, Without<Downed>),
+    >,
+    time: Res<Time>,
+) {
+    for (entity, actions, tf, velocity, mut cooldown, mut sprite) in survivours.iter_mut() {
+        cooldown.tick(time.delta());
+        if !cooldown.finished() || !actions.just_pressed(SurvivourActions::Dodge) {
+            continue;
+        }
+
+        // Roll where the survivour is going, or forward when standing still
+        let direction = if velocity.0 != Vec2::ZERO {
+            velocity.normalize()
+        } else {
+            tf.rotation.mul_vec3(Vec3::X).truncate()
+        };
+
+        cmds.entity(entity).insert(Dodging {
+            timer: Timer::from_seconds(DODGE_DURATION, TimerMode::Once),
+            direction,
+        });
+        // See through while invulnerable
+        sprite.color.set_a(0.5);
+        cooldown.reset();
+    }
 }
 
 fn survivour_walks(
-    mut survivour_actions: Query<
+    mut survivours: Query<
         (
             &ActionState<SurvivourActions>,
             &mut Transform,
             &MovementSpeed,
+            &mut Velocity,
+            &mut Stamina,
+            Option<&mut Dodging>,
+            &mut Sprite,
+            Entity,
         ),
-        (With<Survivour>, Without<HealthHeart>),
+        (With<Survivour>, Without<Downed>),
     >,
-    mut heart_tf: Query<&mut Transform, (With<HealthHeart>, Without<Survivour>)>,
     map_bounds: Query<&MapBounds>,
+    mut cmds: Commands,
     time: Res<Time>,
 ) {
-    // There is only one survivour, but we check if he exists just in case
-    let Ok((actions, mut tf, speed)) = survivour_actions.get_single_mut() else {
-        return;
-    };
-    let mut heart_tf = heart_tf.single_mut();
     // There is only one map bounds
     let map_bounds = map_bounds.single();
 
-    let mut delta = Vec2::splat(0.0);
+    for (actions, mut tf, speed, mut velocity, mut stamina, dodging, mut sprite, entity) in
+        survivours.iter_mut()
+    {
+        let mut direction = Vec2::splat(0.0);
 
-    if actions.pressed(SurvivourActions::Up) {
-        delta += Vec2::Y;
-    }
+        if actions.pressed(SurvivourActions::Up) {
+            direction += Vec2::Y;
+        }
 
-    if actions.pressed(SurvivourActions::Down) {
-        delta += -Vec2::Y;
-    }
+        if actions.pressed(SurvivourActions::Down) {
+            direction += -Vec2::Y;
+        }
+
+        if actions.pressed(SurvivourActions::Left) {
+            direction += -Vec2::X;
+        }
+
+        if actions.pressed(SurvivourActions::Right) {
+            direction += Vec2::X;
+        }
 
-    if actions.pressed(SurvivourActions::Left) {
-        delta += -Vec2::X;
+        if let Some(axis) = actions.axis_pair(SurvivourActions::Move) {
+            direction += axis.xy();
+        }
+
+        // Standing still doesn't use stamina
+        let sprinting = stamina.update(
+            actions.pressed(SurvivourActions::Sprint) && direction != Vec2::ZERO,
+            time.delta_seconds(),
+        );
+        let speed_scale = if sprinting { SPRINT_SCALE } else { 1.0 };
+
+        if let Some(mut dodging) = dodging {
+            // The roll ignores the usual acceleration and goes at a fixed speed
+            velocity.0 = dodging.direction * DODGE_DISTANCE / DODGE_DURATION;
+            if dodging.timer.tick(time.delta()).finished() {
+                velocity.0 = dodging.direction * speed.max_speed;
+                sprite.color.set_a(1.0);
+                cmds.entity(entity).remove::<Dodging>();
+            }
+        } else {
+            speed.steer(&mut velocity, direction, speed_scale, time.delta_seconds());
+        }
+        tf.translation += velocity.extend(0.) * time.delta_seconds();
+
+        // Clamp the player's translation to the map bounds
+        let bounds = map_bounds.half_extents() - Vec2::splat(16.0);
+        let clamped = tf.translation.truncate().clamp(-bounds, bounds);
+        // Don't keep pushing against the edge
+        if clamped.x != tf.translation.x {
+            velocity.x = 0.0;
+        }
+        if clamped.y != tf.translation.y {
+            velocity.y = 0.0;
+        }
+        tf.translation.x = clamped.x;
+        tf.translation.y = clamped.y;
     }
+}
 
-    if actions.pressed(SurvivourActions::Right) {
-        delta += Vec2::X;
+fn dim_downed(mut downed: Query<(&mut Sprite, &mut Velocity), (With<Survivour>, Added<Downed>)>) {
+    for (mut sprite, mut velocity) in downed.iter_mut() {
+        sprite.color = DOWNED_COLOR;
+        velocity.0 = Vec2::ZERO;
     }
+}
 
-    delta *= 15.0;
-
-    tf.translation += delta.extend(0.) * time.delta_seconds() * **speed;
-
-    // Clamp the player's translation to the map bounds
-    tf.translation.x = tf
-        .translation
-        .x
-        .clamp(-map_bounds.x * 32.0 + 16.0, map_bounds.x * 32.0 - 16.0);
-    tf.translation.y = tf
-        .translation
-        .y
-        .clamp(-map_bounds.y * 32.0 + 16.0, map_bounds.y * 32.0 - 16.0);
-    heart_tf.translation.x = tf.translation.x;
-    heart_tf.translation.y = tf.translation.y + 50.0;
+fn revive_downed(
+    mut cmds: Commands,
+    mut downed: Query<(
+        Entity,
+        &Transform,
+        &Player,
+        &mut Downed,
+        &mut Health,
+        &mut Sprite,
+    )>,
+    helpers: Query<&Transform, (With<Survivour>, Without<Downed>)>,
+    time: Res<Time>,
+) {
+    for (entity, tf, player, mut downed, mut health, mut sprite) in downed.iter_mut() {
+        let helped = helpers.iter().any(|helper_tf| {
+            helper_tf
+                .translation
+                .truncate()
+                .distance(tf.translation.truncate())
+                < REVIVE_RADIUS
+        });
+
+        // Walking away loses the progress
+        if !helped {
+            downed.revive.reset();
+            continue;
+        }
+
+        if downed.revive.tick(time.delta()).just_finished() {
+            health.0 = REVIVE_HEALTH;
+            sprite.color = PLAYER_COLORS[player.0];
+            cmds.entity(entity).remove::<Downed>();
+        }
+    }
 }
 
-fn update_heart_health(
-    health: Query<&Health, With<Survivour>>,
-    mut heart_text: Query<&mut Text, With<HealthText>>,
+// The run ends once nobody is left standing
+fn check_all_down(
+    survivours: Query<Option<&Downed>, With<Survivour>>,
+    mut game_state: ResMut<NextState<GameState>>,
 ) {
-    let health = health.single();
-    let mut heart_text = heart_text.single_mut();
-    heart_text.sections[0].value = health.0.to_string();
+    if !survivours.is_empty() && survivours.iter().all(|downed| downed.is_some()) {
+        game_state.set(GameState::GameOver);
+    }
 }
 
 fn look_at_cursor(
-    mut survivour_tf: Query<&mut Transform, With<Survivour>>,
-    coords: Res<MouseWorldCoords>,
+    mut survivours: Query<(&mut Transform, &Crosshair), (With<Survivour>, Without<Downed>)>,
 ) {
-    let Ok(mut survivour_tf) = survivour_tf.get_single_mut() else {
-        return;
-    };
-
-    let direction = (coords.0 - survivour_tf.translation.truncate()).normalize();
-
-    let angle = direction.y.atan2(direction.x);
+    for (mut survivour_tf, crosshair) in survivours.iter_mut() {
+        let direction = crosshair.0 - survivour_tf.translation.truncate();
+        if direction == Vec2::ZERO {
+            continue;
+        }
 
-    survivour_tf.rotation = Quat::from_rotation_z(angle);
-}
+        let angle = direction.y.atan2(direction.x);
 
-fn update_camera(
-    survivour_tf: Query<&Transform, (With<Survivour>, Without<GameCamera>)>,
-    mut world_cam_tf: Query<&mut Transform, (With<GameCamera>, Without<Survivour>)>,
-    window: Query<&Window>,
-    map_bounds: Query<&MapBounds>,
-) {
-    let Ok(survivour_tf) = survivour_tf.get_single() else {
-        return;
-    };
-    let Ok(mut cam_tf) = world_cam_tf.get_single_mut() else {
-        return;
-    };
-    let window = window.single();
-    let map_bounds = map_bounds.single();
-    let window_width = window.width();
-    let window_height = window.height();
-
-    let (x_diff_pos, x_diff_neg) = (
-        map_bounds.x * 32.0 - survivour_tf.translation.x,
-        -map_bounds.x * 32.0 - survivour_tf.translation.x,
-    );
-    let (y_diff_pos, y_diff_neg) = (
-        map_bounds.y * 32.0 - survivour_tf.translation.y,
-        -map_bounds.y * 32.0 - survivour_tf.translation.y,
-    );
-
-    let x_collision =
-        x_diff_pos - window_width / 2.0 > 0.0 && x_diff_neg - -(window_width / 2.0) < 0.0;
-
-    let y_collision =
-        y_diff_pos - window_height / 2.0 > 0.0 && y_diff_neg - -(window_height / 2.0) < 0.0;
-
-    if x_collision {
-        cam_tf.translation.x = survivour_tf.translation.x;
-    }
-    if y_collision {
-        cam_tf.translation.y = survivour_tf.translation.y;
+        survivour_tf.rotation = Quat::from_rotation_z(angle);
     }
 }
 
 fn shoot_bullet(
     mut cmds: Commands,
-    mut survivour_tf: Query<(&Transform, &ActionState<SurvivourActions>, &mut AttackDelay)>,
+    mut survivours: Query<
+        (
+            &Transform,
+            &ActionState<SurvivourActions>,
+            &mut AttackDelay,
+            &mut FireToggle,
+        ),
+        (With<Survivour>, Without<Downed>),
+    >,
     time: Res<Time>,
     graphics: Res<Graphics>,
-    sounds: Res<Sounds>,
-    audio: Res<Audio>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut stats: ResMut<RunStats>,
+    settings: Res<Settings>,
 ) {
-    let Ok((survivour_tf, survivour_actions, mut shoot_delay)) = survivour_tf.get_single_mut()
-    else {
-        return;
-    };
+    for (survivour_tf, survivour_actions, mut shoot_delay, mut fire_toggle) in survivours.iter_mut()
+    {
+        let shooting = match settings.accessibility.fire_mode {
+            FireMode::Hold => {
+                fire_toggle.0 = false;
+                survivour_actions.pressed(SurvivourActions::Shoot)
+            }
+            FireMode::Toggle => {
+                if survivour_actions.just_pressed(SurvivourActions::Shoot) {
+                    fire_toggle.0 = !fire_toggle.0;
+                }
+                fire_toggle.0
+            }
+        };
+
+        shoot_delay.tick(time.delta());
+        if !shoot_delay.finished() || !shooting {
+            continue;
+        }
 
-    shoot_delay.tick(time.delta());
-    if shoot_delay.finished() && survivour_actions.pressed(SurvivourActions::Shoot) {
         let bullet_start_pos = survivour_tf.translation.truncate()
             + survivour_tf.rotation.mul_vec3(Vec3::X * 30.0).truncate();
 
@@ -382,7 +709,8 @@ fn shoot_bullet(
             CollisionSize(Vec2::new(16.0, 16.0)),
         ));
 
-        audio.play(sounds.shoot.clone());
+        sfx.send(PlaySfx(Sfx::Shoot));
+        stats.shots_fired += 1;
         shoot_delay.reset();
     }
 }
@@ -407,16 +735,12 @@ fn cleanup(
     mut cmds: Commands,
     mut window: Query<&mut Window>,
     sv: Query<Entity, With<Survivour>>,
-    health_heart: Query<Entity, With<HealthHeart>>,
     game_cursor: Query<Entity, With<GameCursor>>,
     bullets: Query<Entity, With<Bullet>>,
 ) {
     for entity in sv.iter() {
         cmds.entity(entity).despawn_recursive();
     }
-    for entity in health_heart.iter() {
-        cmds.entity(entity).despawn_recursive();
-    }
     for entity in game_cursor.iter() {
         cmds.entity(entity).despawn_recursive();
     }
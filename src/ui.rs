@@ -1,59 +1,260 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, prelude::*, ui::UiSystem};
 use strum::*;
 
 use crate::{
     assets::{Fonts, Graphics},
-    state::GameState,
+    game_mode::GameMode,
+    locale::{Locale, LocalizedText},
+    sound::{PlaySfx, Sfx},
+    state::{GameState, Overlay},
+    stats::RunStats,
+    waves::{Score, Wave},
 };
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .add_systems(PreUpdate, navigate_menu.after(UiSystem::Focus))
+            .add_systems(
+                Update,
+                (
+                    menu_button_colors,
+                    menu_select_sound,
+                    hide_under_overlay.run_if(state_changed::<Overlay>()),
+                ),
+            );
+
         app.add_systems(OnEnter(GameState::MainMenu), main_menu)
             .add_systems(
                 Update,
-                (button_system, enter_to_play).run_if(in_state(GameState::MainMenu)),
+                (
+                    button_system,
+                    update_game_mode_text.run_if(resource_changed::<GameMode>()),
+                )
+                    .run_if(in_state(GameState::MainMenu).and_then(in_state(Overlay::None))),
             )
             .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu);
 
+        app.add_systems(OnEnter(Overlay::Credits), credits_ui)
+            .add_systems(
+                Update,
+                close_info_screen.run_if(
+                    in_state(Overlay::Credits)
+                        .or_else(in_state(Overlay::HighScores))
+                        .or_else(in_state(Overlay::Achievements)),
+                ),
+            )
+            .add_systems(OnExit(Overlay::Credits), cleanup_info_screen)
+            .add_systems(OnExit(Overlay::HighScores), cleanup_info_screen)
+            .add_systems(OnExit(Overlay::Achievements), cleanup_info_screen);
+
         app.add_systems(OnEnter(GameState::GameOver), game_over_ui)
             .add_systems(
                 Update,
-                update_game_over.run_if(in_state(GameState::GameOver)),
+                game_over_buttons
+                    .run_if(in_state(GameState::GameOver).and_then(in_state(Overlay::None))),
             )
             .add_systems(OnExit(GameState::GameOver), cleanup_game_over);
     }
 }
 
+pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+
+// Position of a button in keyboard and gamepad navigation, starting at 0
+#[derive(Component)]
+pub struct MenuButton(pub usize);
+
+// The button focused with the keyboard or a gamepad
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub usize);
+
+// Menus hidden while an overlay is open, so only the overlay can be navigated
+#[derive(Component)]
+pub struct HideUnderOverlay;
+
+fn navigate_menu(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut focus: ResMut<MenuFocus>,
+    mut buttons: Query<(Entity, &MenuButton, &mut Interaction, &ViewVisibility)>,
+    new_buttons: Query<(), Added<MenuButton>>,
+    mut pressed: Local<Vec<Entity>>,
+) {
+    // Release the buttons pressed last frame, like the mouse button would
+    for entity in pressed.drain(..) {
+        if let Ok((_, _, mut interaction, _)) = buttons.get_mut(entity) {
+            *interaction = Interaction::None;
+        }
+    }
+
+    // A new menu starts on its first button
+    if !new_buttons.is_empty() {
+        focus.0 = 0;
+    }
+
+    let mut count = 0;
+    for (_, button, interaction, visibility) in buttons.iter_mut() {
+        if !visibility.get() {
+            continue;
+        }
+        count = count.max(button.0 + 1);
+
+        // Pointing at a button with the mouse focuses it as well
+        if interaction.is_changed() && *interaction == Interaction::Hovered {
+            focus.0 = button.0;
+        }
+    }
+    if count == 0 {
+        return;
+    }
+
+    let gamepad_pressed = |button_type: GamepadButtonType| {
+        gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == button_type)
+    };
+    // Alt+Enter toggles the fullscreen instead
+    let alt = keyboard_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+
+    if keyboard_input.just_pressed(KeyCode::Up) || gamepad_pressed(GamepadButtonType::DPadUp) {
+        focus.0 = (focus.0 + count - 1) % count;
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) || gamepad_pressed(GamepadButtonType::DPadDown) {
+        focus.0 = (focus.0 + 1) % count;
+    }
+
+    if (!alt && keyboard_input.just_pressed(KeyCode::Return))
+        || gamepad_pressed(GamepadButtonType::South)
+    {
+        for (entity, button, mut interaction, visibility) in buttons.iter_mut() {
+            if visibility.get() && button.0 == focus.0 {
+                *interaction = Interaction::Pressed;
+                pressed.push(entity);
+            }
+        }
+    }
+}
+
+fn menu_select_sound(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<MenuButton>)>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    if buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        sfx.send(PlaySfx(Sfx::MenuSelect));
+    }
+}
+
+fn menu_button_colors(
+    focus: Res<MenuFocus>,
+    mut buttons: Query<(&MenuButton, &Interaction, &mut BackgroundColor)>,
+) {
+    for (button, interaction, mut color) in buttons.iter_mut() {
+        let new_color = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON,
+            _ if button.0 == focus.0 => HOVERED_BUTTON,
+            _ => NORMAL_BUTTON,
+        };
+
+        if color.0 != new_color {
+            color.0 = new_color;
+        }
+    }
+}
+
+fn hide_under_overlay(
+    overlay: Res<State<Overlay>>,
+    mut menus: Query<&mut Visibility, With<HideUnderOverlay>>,
+) {
+    for mut visibility in menus.iter_mut() {
+        *visibility = if *overlay.get() == Overlay::None {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+pub fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    fonts: &Fonts,
+    index: usize,
+    label: impl Into<LocalizedText>,
+    marker: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(480.0),
+                    height: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            MenuButton(index),
+            marker,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 32.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                label.into(),
+            ));
+        });
+}
+
 // All the stuff related to the main menu
 #[derive(Component)]
 pub struct MainMenu;
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
-
-#[derive(Component, EnumIter, Copy, Clone)]
+#[derive(Component, EnumIter, Copy, Clone, PartialEq, Eq)]
 enum MainMenuButtons {
     Play,
+    GameMode,
+    Settings,
+    HighScores,
+    Achievements,
+    Credits,
     Quit,
 }
 
-impl std::fmt::Display for MainMenuButtons {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                MainMenuButtons::Play => "Play",
-                MainMenuButtons::Quit => "Quit",
-            }
-        )
+impl MainMenuButtons {
+    // Translation key of the button label
+    pub fn key(&self) -> &'static str {
+        match self {
+            MainMenuButtons::Play => "menu.play",
+            MainMenuButtons::GameMode => "menu.mode",
+            MainMenuButtons::Settings => "menu.settings",
+            MainMenuButtons::HighScores => "menu.high-scores",
+            MainMenuButtons::Achievements => "menu.achievements",
+            MainMenuButtons::Credits => "menu.credits",
+            MainMenuButtons::Quit => "menu.quit",
+        }
     }
 }
 
-fn main_menu(mut commands: Commands, graphics: Res<Graphics>, fonts: Res<Fonts>) {
+fn main_menu(
+    mut commands: Commands,
+    graphics: Res<Graphics>,
+    fonts: Res<Fonts>,
+    game_mode: Res<GameMode>,
+) {
     commands
         .spawn((
             MainMenu,
@@ -81,74 +282,89 @@ fn main_menu(mut commands: Commands, graphics: Res<Graphics>, fonts: Res<Fonts>)
     commands
         .spawn((
             MainMenu,
+            HideUnderOverlay,
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.),
                     height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
                     ..default()
                 },
                 ..default()
             },
         ))
         .with_children(|ui| {
-            ui.spawn(TextBundle {
-                text: Text {
-                    sections: vec![TextSection {
-                        value: "Press Enter to start!".to_string(),
-                        style: TextStyle {
-                            font: fonts.zombiecontrol.clone(),
-                            font_size: 100.0,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                        },
-                    }],
-                    alignment: TextAlignment::Center,
+            ui.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.zombiecontrol.clone(),
+                        font_size: 100.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(24.0)),
                     ..default()
-                },
-                ..default()
-            });
+                }),
+                LocalizedText::new("menu.title"),
+            ));
+
+            for (index, button) in MainMenuButtons::iter().enumerate() {
+                let label = match button {
+                    MainMenuButtons::GameMode => game_mode_label(*game_mode),
+                    _ => button.key().into(),
+                };
+                spawn_menu_button(ui, &fonts, index, label, button);
+            }
         });
 }
 
-fn enter_to_play(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut next_state: ResMut<NextState<GameState>>,
+fn game_mode_label(game_mode: GameMode) -> LocalizedText {
+    LocalizedText::new(MainMenuButtons::GameMode.key()).with_key_arg("mode", game_mode.key())
+}
+
+fn update_game_mode_text(
+    game_mode: Res<GameMode>,
+    buttons: Query<(&MainMenuButtons, &Children)>,
+    mut texts: Query<&mut LocalizedText>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Return) {
-        next_state.set(GameState::Playing);
+    for (button, children) in buttons.iter() {
+        if *button != MainMenuButtons::GameMode {
+            continue;
+        }
+
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                *text = game_mode_label(*game_mode);
+            }
+        }
     }
 }
 
 fn button_system(
-    mut interaction_query: Query<
-        (&Interaction, &MainMenuButtons, &mut BackgroundColor),
-        (Changed<Interaction>, With<Button>),
-    >,
+    interaction_query: Query<(&Interaction, &MainMenuButtons), Changed<Interaction>>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut overlay: ResMut<NextState<Overlay>>,
+    mut game_mode: ResMut<GameMode>,
     mut app_exit: EventWriter<AppExit>,
 ) {
-    for (interaction, button, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
-                match button {
-                    MainMenuButtons::Play => {
-                        println!("Play button pressed");
-                        next_state.set(GameState::Playing);
-                    }
-                    MainMenuButtons::Quit => {
-                        println!("Quit button pressed");
-                        app_exit.send(AppExit);
-                    }
-                }
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-            }
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MainMenuButtons::Play => next_state.set(GameState::Playing),
+            MainMenuButtons::GameMode => *game_mode = game_mode.next(),
+            MainMenuButtons::Settings => overlay.set(Overlay::Settings),
+            MainMenuButtons::HighScores => overlay.set(Overlay::HighScores),
+            MainMenuButtons::Achievements => overlay.set(Overlay::Achievements),
+            MainMenuButtons::Credits => overlay.set(Overlay::Credits),
+            MainMenuButtons::Quit => app_exit.send(AppExit),
         }
     }
 }
@@ -159,11 +375,176 @@ fn cleanup_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenu>
     }
 }
 
+// Simple screens with a title, a few lines of text and a back button.
+// The high scores screen is filled by `high_scores`
+#[derive(Component)]
+struct InfoScreen;
+
+#[derive(Component)]
+struct InfoBackButton;
+
+pub fn spawn_info_screen(
+    commands: &mut Commands,
+    fonts: &Fonts,
+    title: &str,
+    lines: &[impl AsRef<str>],
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: fonts.zombiecontrol.clone(),
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn((
+            InfoScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                // Draw on top of the screen that opened it
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|ui| {
+            ui.spawn(TextBundle::from_section(title, text_style(72.0)));
+
+            for line in lines {
+                ui.spawn(TextBundle::from_section(line.as_ref(), text_style(28.0)));
+            }
+
+            spawn_menu_button(ui, fonts, 0, "menu.back", InfoBackButton);
+        });
+}
+
+fn credits_ui(mut commands: Commands, fonts: Res<Fonts>, locale: Res<Locale>) {
+    let lines = [
+        "credits.original",
+        "credits.heart",
+        "credits.icon",
+        "credits.cheatbook",
+    ]
+    .map(|key| locale.get(key));
+
+    spawn_info_screen(&mut commands, &fonts, &locale.get("credits.title"), &lines);
+}
+
+fn close_info_screen(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    back_button: Query<&Interaction, (Changed<Interaction>, With<InfoBackButton>)>,
+    mut overlay: ResMut<NextState<Overlay>>,
+) {
+    let back_pressed = back_button
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    let gamepad_back = gamepad_buttons
+        .get_just_pressed()
+        .any(|button| button.button_type == GamepadButtonType::East);
+
+    if back_pressed || gamepad_back || keyboard_input.just_pressed(KeyCode::Escape) {
+        overlay.set(Overlay::None);
+    }
+}
+
+fn cleanup_info_screen(mut commands: Commands, query: Query<Entity, With<InfoScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 // All the stuff related to the game over screen
 #[derive(Component)]
 pub struct GameOver;
 
-fn game_over_ui(mut commands: Commands, graphics: Res<Graphics>, fonts: Res<Fonts>) {
+#[derive(Component, EnumIter, Copy, Clone)]
+enum GameOverButtons {
+    Restart,
+    MainMenu,
+    HighScores,
+}
+
+impl GameOverButtons {
+    // Translation key of the button label
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameOverButtons::Restart => "menu.restart",
+            GameOverButtons::MainMenu => "menu.main-menu",
+            GameOverButtons::HighScores => "menu.high-scores",
+        }
+    }
+}
+
+// One line per statistic, label and value
+fn run_summary(
+    score: &Score,
+    wave: &Wave,
+    stats: &RunStats,
+    locale: &Locale,
+) -> Vec<(String, String)> {
+    let seconds = stats.time_survived.as_secs();
+    let accuracy = stats
+        .accuracy()
+        .map(|accuracy| format!("{:.0}%", accuracy * 100.0))
+        .unwrap_or_else(|| "-".to_string());
+
+    let count_kills = |key: &str, count: i32| locale.plural(key, count as i64, &[]);
+    let kills = locale.format(
+        "summary.kills-list",
+        &[
+            ("chasers", &count_kills("kills.chasers", stats.kills.chaser)),
+            (
+                "crawlers",
+                &count_kills("kills.crawlers", stats.kills.crawler),
+            ),
+            (
+                "bloaters",
+                &count_kills("kills.bloaters", stats.kills.bloater),
+            ),
+        ],
+    );
+
+    [
+        ("summary.score", score.0.to_string()),
+        ("summary.wave", wave.count.to_string()),
+        (
+            "summary.time",
+            format!("{}:{:02}", seconds / 60, seconds % 60),
+        ),
+        ("summary.kills", kills),
+        ("summary.shots", stats.shots_fired.to_string()),
+        ("summary.accuracy", accuracy),
+        ("summary.damage", stats.damage_taken.to_string()),
+        ("summary.pickups", stats.pickups_collected.to_string()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (locale.get(key), value))
+    .collect()
+}
+
+fn game_over_ui(
+    mut commands: Commands,
+    graphics: Res<Graphics>,
+    fonts: Res<Fonts>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    stats: Res<RunStats>,
+    locale: Res<Locale>,
+) {
+    let text_style = |font_size: f32| TextStyle {
+        font: fonts.zombiecontrol.clone(),
+        font_size,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
     commands
         .spawn((
             GameOver,
@@ -191,56 +572,81 @@ fn game_over_ui(mut commands: Commands, graphics: Res<Graphics>, fonts: Res<Font
     commands
         .spawn((
             GameOver,
+            HideUnderOverlay,
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.),
                     height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
                     ..default()
                 },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
                 ..default()
             },
         ))
         .with_children(|ui| {
+            ui.spawn((
+                TextBundle::from_section("", text_style(72.0)),
+                LocalizedText::new("game-over.title"),
+            ));
+
             ui.spawn(NodeBundle {
                 style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    justify_content: JustifyContent::SpaceEvenly,
                     flex_direction: FlexDirection::Column,
-                    align_items: AlignItems::Center,
+                    margin: UiRect::vertical(Val::Px(12.0)),
+                    row_gap: Val::Px(4.0),
                     ..default()
                 },
                 ..default()
             })
-            .with_children(|ui| {
-                ui.spawn(TextBundle::from_section(
-                    "Game Over",
-                    TextStyle {
-                        font: fonts.zombiecontrol.clone(),
-                        font_size: 72.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                ));
-                ui.spawn(TextBundle::from_section(
-                    "Press Space to restart!",
-                    TextStyle {
-                        font: fonts.zombiecontrol.clone(),
-                        font_size: 72.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                ));
+            .with_children(|table| {
+                for (label, value) in run_summary(&score, &wave, &stats, &locale) {
+                    table
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(24.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            row.spawn(TextBundle {
+                                text: Text::from_section(label, text_style(28.0)),
+                                style: Style {
+                                    width: Val::Px(260.0),
+                                    ..default()
+                                },
+                                ..default()
+                            });
+                            row.spawn(TextBundle::from_section(value, text_style(28.0)));
+                        });
+                }
             });
+
+            for (index, button) in GameOverButtons::iter().enumerate() {
+                spawn_menu_button(ui, &fonts, index, button.key(), button);
+            }
         });
 }
 
-fn update_game_over(
+fn game_over_buttons(
+    interaction_query: Query<(&Interaction, &GameOverButtons), Changed<Interaction>>,
     mut next_state: ResMut<NextState<GameState>>,
-    keyboard_input: Res<Input<KeyCode>>,
+    mut overlay: ResMut<NextState<Overlay>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        next_state.set(GameState::Playing);
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            GameOverButtons::Restart => next_state.set(GameState::Playing),
+            GameOverButtons::MainMenu => next_state.set(GameState::MainMenu),
+            GameOverButtons::HighScores => overlay.set(Overlay::HighScores),
+        }
     }
 }
 
@@ -1,33 +1,25 @@
 use crate::zombies::ZombieBundle;
 use crate::{
-    assets::{Fonts, Graphics},
+    assets::Graphics,
+    game_mode::Difficulty,
     map::MapBounds,
-    state::GameState,
+    settings::Settings,
+    state::{gameplay_running, GameState},
     zombies::Zombie,
 };
 use bevy::prelude::*;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 pub struct WavesPlugin;
 
 impl Plugin for WavesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), init_wave_stats);
-        app.add_systems(
-            Update,
-            update_wave_text
-                .run_if(in_state(GameState::Playing).and_then(resource_changed::<Wave>())),
-        )
-        .add_systems(
-            Update,
-            update_zombies_remaining.run_if(in_state(GameState::Playing)),
-        )
-        .add_systems(Update, update_score.run_if(in_state(GameState::Playing)))
-        .add_systems(
-            Update,
-            generate_wave
-                .run_if(in_state(GameState::Playing).and_then(resource_equals(ZombieCount::ZERO))),
-        );
+        app.add_systems(OnEnter(GameState::Playing), init_wave_stats)
+            .add_systems(
+                Update,
+                generate_wave.run_if(gameplay_running.and_then(resource_equals(ZombieCount::ZERO))),
+            );
     }
 }
 
@@ -50,23 +42,42 @@ impl Default for Wave {
 #[derive(Resource, Default)]
 pub struct Score(pub i32);
 
-impl Score {
-    pub fn increase(&mut self, zombie: &Zombie) {
-        match zombie {
-            Zombie::Chaser => self.0 += 1,
-            Zombie::Crawler => self.0 += 3,
-            Zombie::Bloater => self.0 += 5,
+// Chosen when the run starts, changing the settings mid-run doesn't affect it
+#[derive(Resource)]
+pub struct RunSetup {
+    // Seeds the wave generation, the same seed spawns the same waves
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    rng: StdRng,
+}
+
+impl RunSetup {
+    fn new(difficulty: Difficulty) -> Self {
+        let seed = rand::random();
+        Self {
+            seed,
+            difficulty,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 }
 
-#[derive(Component)]
-pub struct ScoreText;
+impl Score {
+    pub fn points(zombie: &Zombie) -> i32 {
+        match zombie {
+            Zombie::Chaser => 1,
+            Zombie::Crawler => 3,
+            Zombie::Bloater => 5,
+        }
+    }
 
-#[derive(Component)]
-pub struct WaveText;
+    pub fn increase(&mut self, zombie: &Zombie) {
+        self.0 += Self::points(zombie);
+    }
+}
 
-#[derive(Resource, Default, PartialEq, Eq, Debug, Clone)]
+#[derive(Resource, Default, PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ZombieCount {
     pub chaser: i32,
     pub crawler: i32,
@@ -84,6 +95,22 @@ impl ZombieCount {
         self.chaser + self.crawler + self.bloater
     }
 
+    pub fn count(&self, zombie: Zombie) -> i32 {
+        match zombie {
+            Zombie::Chaser => self.chaser,
+            Zombie::Crawler => self.crawler,
+            Zombie::Bloater => self.bloater,
+        }
+    }
+
+    pub fn increase_count(&mut self, zombie: &Zombie) {
+        match zombie {
+            Zombie::Chaser => self.chaser += 1,
+            Zombie::Crawler => self.crawler += 1,
+            Zombie::Bloater => self.bloater += 1,
+        }
+    }
+
     pub fn decrease_count(&mut self, zombie: &Zombie) {
         match zombie {
             Zombie::Chaser => self.chaser -= 1,
@@ -93,63 +120,12 @@ impl ZombieCount {
     }
 }
 
-fn init_wave_stats(mut cmds: Commands, fonts: Res<Fonts>) {
+fn init_wave_stats(mut cmds: Commands, settings: Res<Settings>) {
+    cmds.insert_resource(RunSetup::new(settings.gameplay.difficulty));
     cmds.insert_resource(Wave::default());
     cmds.insert_resource(ZombieCount::default());
     cmds.insert_resource(FirstWave::default());
     cmds.insert_resource(Score::default());
-
-    // Spawn the game HUD
-    cmds.spawn(NodeBundle {
-        style: Style {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            justify_content: JustifyContent::SpaceBetween,
-            padding: UiRect {
-                left: Val::Px(20.0),
-                right: Val::Px(20.0),
-                top: Val::Px(20.0),
-                bottom: Val::Px(20.0),
-            },
-            ..default()
-        },
-        ..default()
-    })
-    .with_children(|parent| {
-        parent.spawn((
-            TextBundle::from_section(
-                format!("Wave {}", 1),
-                TextStyle {
-                    font: fonts.zombiecontrol.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                },
-            ),
-            WaveText,
-        ));
-        parent.spawn((
-            TextBundle::from_section(
-                "Score: 0",
-                TextStyle {
-                    font: fonts.zombiecontrol.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                },
-            ),
-            ScoreText,
-        ));
-        parent.spawn((
-            TextBundle::from_section(
-                format!("Zombies Left {}", 0),
-                TextStyle {
-                    font: fonts.zombiecontrol.clone(),
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                },
-            ),
-            ZombieLeftText,
-        ));
-    });
 }
 
 #[derive(Resource)]
@@ -167,6 +143,7 @@ fn generate_wave(
     map_bounds: Query<&MapBounds>,
     graphics: Res<Graphics>,
     mut first_wave: ResMut<FirstWave>,
+    mut run: ResMut<RunSetup>,
     time: Res<Time>,
 ) {
     if !wave.timer.tick(time.delta()).finished() {
@@ -174,10 +151,16 @@ fn generate_wave(
     }
     let map_bounds = map_bounds.single();
 
-    let mut rng = rand::thread_rng();
-    let chaser_count = rng.gen_range(1..=wave.count * 3);
-    let crawler_count = rng.gen_range(1..=wave.count * 2);
-    let bloater_count = rng.gen_range(1..=wave.count);
+    let multiplier = run.difficulty.zombie_multiplier();
+    let max_count = |per_wave: i32| {
+        ((wave.count * per_wave) as f32 * multiplier)
+            .ceil()
+            .max(1.0) as i32
+    };
+    let rng = &mut run.rng;
+    let chaser_count = rng.gen_range(1..=max_count(3));
+    let crawler_count = rng.gen_range(1..=max_count(2));
+    let bloater_count = rng.gen_range(1..=max_count(1));
     commands.insert_resource(ZombieCount {
         chaser: chaser_count,
         crawler: crawler_count,
@@ -216,28 +199,3 @@ fn generate_wave(
     }
     wave.timer.reset();
 }
-
-#[derive(Component)]
-pub struct ZombieLeftText;
-
-fn update_wave_text(wave: Res<Wave>, mut wave_text: Query<&mut Text, With<WaveText>>) {
-    for mut text in wave_text.iter_mut() {
-        text.sections[0].value = format!("Wave {}", wave.count);
-    }
-}
-
-fn update_zombies_remaining(
-    zombies: Res<ZombieCount>,
-    mut zombie_left_text: Query<&mut Text, With<ZombieLeftText>>,
-) {
-    let zombies_count = zombies.total();
-    for mut text in zombie_left_text.iter_mut() {
-        text.sections[0].value = format!("Zombies Left: {}", zombies_count);
-    }
-}
-
-fn update_score(score: Res<Score>, mut score_text: Query<&mut Text, With<ScoreText>>) {
-    for mut text in score_text.iter_mut() {
-        text.sections[0].value = format!("Score: {}", score.0);
-    }
-}
@@ -1,16 +1,17 @@
 use bevy::utils::Duration;
-use std::f32::consts::PI;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::Graphics,
     collision::{BloodTimer, CollisionSize},
-    combat::{AttackDelay, CombatBundle, Health},
-    movement::MovementSpeed,
+    combat::{AttackDelay, CombatBundle, Health, MaxHealth},
+    movement::{MovementSpeed, Velocity},
     powerups::PowerupSpawnChance,
-    state::GameState,
-    survivour::Survivour,
+    settings::Settings,
+    state::{gameplay_running, GameState},
+    survivour::{Downed, Survivour},
     waves::ZombieCount,
 };
 
@@ -18,23 +19,60 @@ pub struct ZombiesPlugin;
 
 impl Plugin for ZombiesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, zombies_walk.run_if(in_state(GameState::Playing)))
-            .add_systems(OnExit(GameState::Playing), (despawn_zombies, despawn_blood));
+        app.add_event::<ZombieKilled>();
+
+        app.add_systems(
+            Update,
+            (zombies_walk, spawn_health_bars, update_health_bars)
+                .chain()
+                .run_if(gameplay_running),
+        )
+        .add_systems(OnExit(GameState::Playing), (despawn_zombies, despawn_blood));
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Zombie {
     Chaser,
     Crawler,
     Bloater,
 }
 
+impl Zombie {
+    // Tougher zombies get a distinct frame around their health bar
+    pub fn is_special(&self) -> bool {
+        matches!(self, Zombie::Bloater)
+    }
+}
+
+// Sent when a bullet finishes a zombie off
+#[derive(Event)]
+pub struct ZombieKilled(pub Zombie);
+
+// Kept apart from the zombie, so it doesn't turn with it
+#[derive(Component)]
+struct HealthBar {
+    zombie: Entity,
+}
+
+#[derive(Component)]
+struct HealthBarFill;
+
+const HEALTH_BAR_SIZE: Vec2 = Vec2::new(40.0, 5.0);
+const HEALTH_BAR_OFFSET: f32 = 40.0;
+const HEALTH_BAR_Z: f32 = 4.5;
+const HEALTH_BAR_FRAME_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const SPECIAL_FRAME_COLOR: Color = Color::rgb(0.9, 0.7, 0.1);
+const HEALTH_BAR_EMPTY_COLOR: Color = Color::rgb(0.3, 0.1, 0.1);
+const HEALTH_BAR_COLOR: Color = Color::rgb(0.8, 0.15, 0.15);
+
 #[derive(Bundle)]
 pub struct ZombieBundle {
     pub sprite_bundle: SpriteBundle,
     pub zombie: Zombie,
     pub movement_speed: MovementSpeed,
+    pub velocity: Velocity,
+    pub max_health: MaxHealth,
     pub combat_bundle: CombatBundle,
     pub collision_size: CollisionSize,
     pub powerup_spawn_chance: PowerupSpawnChance,
@@ -55,7 +93,13 @@ impl ZombieBundle {
                 ..default()
             },
             zombie: Zombie::Chaser,
-            movement_speed: MovementSpeed { speed: 100.0 },
+            movement_speed: MovementSpeed {
+                max_speed: 100.0,
+                acceleration: 400.0,
+                friction: 400.0,
+            },
+            velocity: Velocity::default(),
+            max_health: MaxHealth(1),
             combat_bundle: CombatBundle {
                 health: Health(1),
                 attack_delay: AttackDelay {
@@ -80,7 +124,13 @@ impl ZombieBundle {
                 ..default()
             },
             zombie: Zombie::Crawler,
-            movement_speed: MovementSpeed { speed: 80.0 },
+            movement_speed: MovementSpeed {
+                max_speed: 80.0,
+                acceleration: 400.0,
+                friction: 400.0,
+            },
+            velocity: Velocity::default(),
+            max_health: MaxHealth(3),
             combat_bundle: CombatBundle {
                 health: Health(3),
                 attack_delay: AttackDelay {
@@ -104,7 +154,13 @@ impl ZombieBundle {
                 ..default()
             },
             zombie: Zombie::Bloater,
-            movement_speed: MovementSpeed { speed: 60.0 },
+            movement_speed: MovementSpeed {
+                max_speed: 60.0,
+                acceleration: 400.0,
+                friction: 400.0,
+            },
+            velocity: Velocity::default(),
+            max_health: MaxHealth(5),
             combat_bundle: CombatBundle {
                 health: Health(5),
                 attack_delay: AttackDelay {
@@ -118,27 +174,125 @@ impl ZombieBundle {
 }
 
 fn zombies_walk(
-    survivour: Query<&Transform, (With<Survivour>, Without<Zombie>)>,
-    mut zombies: Query<(&mut Transform, &MovementSpeed), (With<Zombie>, Without<Survivour>)>,
+    survivours: Query<&Transform, (With<Survivour>, Without<Downed>, Without<Zombie>)>,
+    mut zombies: Query<
+        (&mut Transform, &MovementSpeed, &mut Velocity),
+        (With<Zombie>, Without<Survivour>),
+    >,
     time: Res<Time>,
 ) {
-    let Ok(survivour_transform) = survivour.get_single() else {
-        return;
-    };
-
-    for (mut zombie_transform, speed) in zombies.iter_mut() {
-        // Calculate the angle between the zombie and the survivour
-        let mut angle = (survivour_transform.translation.y - zombie_transform.translation.y)
-            .atan2(survivour_transform.translation.x - zombie_transform.translation.x);
-        if angle < 0.0 {
-            angle += 2.0 * PI;
-        }
+    for (mut zombie_transform, speed, mut velocity) in zombies.iter_mut() {
+        let zombie_pos = zombie_transform.translation.truncate();
 
-        zombie_transform.translation.x += **speed * angle.cos() * time.delta_seconds();
-        zombie_transform.translation.y += **speed * angle.sin() * time.delta_seconds();
+        // Go after the closest survivour still standing
+        let target = survivours
+            .iter()
+            .map(|tf| tf.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(zombie_pos)
+                    .total_cmp(&b.distance_squared(zombie_pos))
+            });
+        let direction = target
+            .map(|target| (target - zombie_pos).normalize_or_zero())
+            .unwrap_or(Vec2::ZERO);
+
+        speed.steer(&mut velocity, direction, 1.0, time.delta_seconds());
+        zombie_transform.translation += velocity.extend(0.0) * time.delta_seconds();
 
         // Rotate the zombie to face the survivour
-        zombie_transform.rotation = Quat::from_rotation_z(angle);
+        if direction != Vec2::ZERO {
+            zombie_transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+        }
+    }
+}
+
+fn spawn_health_bars(mut commands: Commands, zombies: Query<(Entity, &Zombie), Added<Zombie>>) {
+    for (entity, zombie) in zombies.iter() {
+        let (frame_color, border) = if zombie.is_special() {
+            (SPECIAL_FRAME_COLOR, 2.0)
+        } else {
+            (HEALTH_BAR_FRAME_COLOR, 1.0)
+        };
+
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: frame_color,
+                        custom_size: Some(HEALTH_BAR_SIZE + Vec2::splat(border * 2.0)),
+                        ..default()
+                    },
+                    // Shown once the zombie takes damage
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                HealthBar { zombie: entity },
+            ))
+            .with_children(|bar| {
+                bar.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: HEALTH_BAR_EMPTY_COLOR,
+                        custom_size: Some(HEALTH_BAR_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                    ..default()
+                });
+                bar.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: HEALTH_BAR_COLOR,
+                            custom_size: Some(HEALTH_BAR_SIZE),
+                            anchor: bevy::sprite::Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(-HEALTH_BAR_SIZE.x / 2.0, 0.0, 0.02),
+                        ..default()
+                    },
+                    HealthBarFill,
+                ));
+            });
+    }
+}
+
+fn update_health_bars(
+    mut commands: Commands,
+    zombies: Query<(&Transform, &Health, &MaxHealth), With<Zombie>>,
+    mut bars: Query<
+        (
+            Entity,
+            &HealthBar,
+            &Children,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        Without<Zombie>,
+    >,
+    mut fills: Query<&mut Transform, (With<HealthBarFill>, Without<HealthBar>, Without<Zombie>)>,
+    settings: Res<Settings>,
+) {
+    for (entity, bar, children, mut transform, mut visibility) in bars.iter_mut() {
+        // The zombie died or was despawned
+        let Ok((zombie_transform, health, max_health)) = zombies.get(bar.zombie) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        transform.translation = (zombie_transform.translation.truncate()
+            + Vec2::Y * HEALTH_BAR_OFFSET)
+            .extend(HEALTH_BAR_Z);
+        *visibility = if settings.gameplay.health_bars && health.0 < max_health.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+
+        let fraction = (health.0 as f32 / max_health.0 as f32).clamp(0.0, 1.0);
+        for &child in children.iter() {
+            if let Ok(mut fill) = fills.get_mut(child) {
+                fill.scale.x = fraction;
+            }
+        }
     }
 }
 
@@ -151,11 +305,15 @@ fn despawn_blood(mut commands: Commands, blood: Query<(Entity, &BloodTimer)>) {
 fn despawn_zombies(
     mut commands: Commands,
     zombies: Query<Entity, With<Zombie>>,
+    health_bars: Query<Entity, With<HealthBar>>,
     mut zombie_count: ResMut<ZombieCount>,
 ) {
     for zombie in zombies.iter() {
         commands.entity(zombie).despawn();
     }
+    for bar in health_bars.iter() {
+        commands.entity(bar).despawn_recursive();
+    }
     zombie_count.chaser = 0;
     zombie_count.crawler = 0;
     zombie_count.bloater = 0;